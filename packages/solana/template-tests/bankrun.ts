@@ -0,0 +1,148 @@
+// Shared setup for the transaction-level suites in templates/*/tests. run.ts builds each template
+// into TEMPLATE_WORKSPACE before mocha loads the suite, so every program listed there is deployed.
+import { readFileSync } from "fs";
+import { join } from "path";
+import { Idl, Program } from "@coral-xyz/anchor";
+import { BankrunProvider } from "anchor-bankrun";
+import { Clock, ProgramTestContext, startAnchor } from "solana-bankrun";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+export let context: ProgramTestContext;
+export let provider: BankrunProvider;
+
+function workspace(): string {
+  const dir = process.env.TEMPLATE_WORKSPACE;
+  if (!dir) throw new Error("TEMPLATE_WORKSPACE is not set; run the suites through run.ts");
+  return dir;
+}
+
+// Starts a fresh bank and returns a client for `name`, the program's crate name.
+export async function startTemplate(name: string): Promise<Program> {
+  context = await startAnchor(workspace(), [], []);
+  provider = new BankrunProvider(context);
+  return loadProgram(name);
+}
+
+// A client for another program deployed in the same bank, such as a fixture.
+export function loadProgram(name: string): Program {
+  const path = join(workspace(), "target/idl", `${name}.json`);
+  const idl: Idl = JSON.parse(readFileSync(path, "utf-8"));
+  return new Program(idl, provider);
+}
+
+export async function expectError(promise: Promise<unknown>, code: string) {
+  try {
+    await promise;
+  } catch (err: any) {
+    const text = [err?.error?.errorCode?.code, err?.message, ...(err?.logs ?? [])].join("\n");
+    expect(text).to.include(code);
+    // Failed transactions stay in the status cache; a fresh blockhash lets a retry through.
+    await nextSlot();
+    return;
+  }
+  expect.fail(`expected ${code}`);
+}
+
+export async function send(ixs: TransactionInstruction[], signers: Keypair[] = []) {
+  await provider.sendAndConfirm(new Transaction().add(...ixs), signers);
+}
+
+export function fund(wallet: Keypair) {
+  context.setAccount(wallet.publicKey, {
+    lamports: 10_000_000_000,
+    data: Buffer.alloc(0),
+    owner: SystemProgram.programId,
+    executable: false,
+  });
+}
+
+// A new keypair with 10 SOL.
+export function newWallet(): Keypair {
+  const keypair = Keypair.generate();
+  fund(keypair);
+  return keypair;
+}
+
+export async function nextSlot() {
+  const clock = await context.banksClient.getClock();
+  context.warpToSlot(clock.slot + 1n);
+}
+
+export async function warp(seconds: number) {
+  const clock = await context.banksClient.getClock();
+  context.setClock(
+    new Clock(
+      clock.slot + 1n,
+      clock.epochStartTimestamp,
+      clock.epoch,
+      clock.leaderScheduleEpoch,
+      clock.unixTimestamp + BigInt(seconds)
+    )
+  );
+}
+
+export async function now(): Promise<bigint> {
+  return (await context.banksClient.getClock()).unixTimestamp;
+}
+
+export async function exists(account: PublicKey): Promise<boolean> {
+  return (await context.banksClient.getAccount(account)) !== null;
+}
+
+export async function lamports(account: PublicKey): Promise<number> {
+  return Number((await context.banksClient.getAccount(account))!.lamports);
+}
+
+export async function tokenBalance(account: PublicKey): Promise<bigint> {
+  const info = await context.banksClient.getAccount(account);
+  return Buffer.from(info!.data).readBigUInt64LE(64);
+}
+
+// A zero-decimal mint whose authority is the provider wallet.
+export async function createMint(): Promise<PublicKey> {
+  const mint = Keypair.generate();
+  const rent = await context.banksClient.getRent();
+  await send(
+    [
+      SystemProgram.createAccount({
+        fromPubkey: provider.wallet.publicKey,
+        newAccountPubkey: mint.publicKey,
+        lamports: Number(rent.minimumBalance(BigInt(MINT_SIZE))),
+        space: MINT_SIZE,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      createInitializeMint2Instruction(mint.publicKey, 0, provider.wallet.publicKey, null),
+    ],
+    [mint]
+  );
+  return mint.publicKey;
+}
+
+// Mints into the owner's ATA, creating it first if needed.
+export async function mintTo(
+  mint: PublicKey,
+  owner: PublicKey,
+  amount: number
+): Promise<PublicKey> {
+  const ata = getAssociatedTokenAddressSync(mint, owner, true);
+  await send([
+    createAssociatedTokenAccountIdempotentInstruction(provider.wallet.publicKey, ata, owner, mint),
+    createMintToInstruction(mint, ata, provider.wallet.publicKey, amount),
+  ]);
+  return ata;
+}
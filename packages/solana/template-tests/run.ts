@@ -0,0 +1,113 @@
+// Builds every template that has a tests/ directory and runs its suites under mocha.
+// Programs in tests/fixtures/*.rs are built and deployed alongside the template's own program.
+// Pass template ids to run a subset: `npm test -- pda-vault multisig-treasury`.
+import { spawnSync } from "child_process";
+import { existsSync } from "fs";
+import { copyFile, mkdir, mkdtemp, readdir, readFile, rm, writeFile } from "fs/promises";
+import { tmpdir } from "os";
+import { basename, join } from "path";
+import { Keypair } from "@solana/web3.js";
+import { ProgramCompiler } from "../../../apps/runner/src/services/compiler";
+
+const TEMPLATES_DIR = join(__dirname, "../templates");
+
+interface BuiltProgram {
+  name: string;
+  programId: string;
+  workspace: string;
+}
+
+// Template ids are placeholders, so each build is given a fresh program id.
+async function build(
+  compiler: ProgramCompiler,
+  id: string,
+  source: string,
+  root: string
+): Promise<BuiltProgram> {
+  const programId = Keypair.generate().publicKey.toBase58();
+  const code = (await readFile(source, "utf-8")).replace(
+    /declare_id!\("[^"]*"\)/,
+    `declare_id!("${programId}")`
+  );
+  const workspace = join(root, "build", id);
+  const result = await compiler.compile(id, code, workspace);
+  if (!result.success) {
+    throw new Error(`Failed to build ${id}: ${result.error}`);
+  }
+  return { name: id.replace(/-/g, "_"), programId, workspace };
+}
+
+// Gathers the built programs into one Anchor workspace for startAnchor.
+async function link(root: string, programs: BuiltProgram[]): Promise<void> {
+  await mkdir(join(root, "target/deploy"), { recursive: true });
+  await mkdir(join(root, "target/idl"), { recursive: true });
+  for (const { name, workspace } of programs) {
+    for (const file of [`deploy/${name}.so`, `idl/${name}.json`]) {
+      await copyFile(join(workspace, "target", file), join(root, "target", file));
+    }
+  }
+  const entries = programs.map(({ name, programId }) => `${name} = "${programId}"`);
+  const anchorToml = ["[programs.localnet]", ...entries].join("\n");
+  await writeFile(join(root, "Anchor.toml"), anchorToml, "utf-8");
+}
+
+function runSuites(root: string, suites: string[]): boolean {
+  const mocha = require.resolve("mocha/bin/mocha.js");
+  const { status } = spawnSync(
+    process.execPath,
+    [mocha, "--require", "tsx/cjs", "--timeout", "600000", ...suites],
+    {
+      cwd: __dirname,
+      stdio: "inherit",
+      env: {
+        ...process.env,
+        TEMPLATE_WORKSPACE: root,
+        SBF_OUT_DIR: join(root, "target/deploy"),
+        // The suites live outside this package, so point them at its dependencies.
+        NODE_PATH: join(__dirname, "node_modules"),
+      },
+    }
+  );
+  return status === 0;
+}
+
+async function main() {
+  const only = process.argv.slice(2);
+  const compiler = new ProgramCompiler();
+  let failed = false;
+
+  for (const id of (await readdir(TEMPLATES_DIR)).sort()) {
+    const testsDir = join(TEMPLATES_DIR, id, "tests");
+    if (!existsSync(testsDir) || (only.length > 0 && !only.includes(id))) continue;
+
+    const suites = (await readdir(testsDir))
+      .filter((file) => file.endsWith(".ts"))
+      .map((file) => join(testsDir, file));
+    const fixturesDir = join(testsDir, "fixtures");
+    const fixtures = existsSync(fixturesDir)
+      ? (await readdir(fixturesDir)).filter((file) => file.endsWith(".rs"))
+      : [];
+
+    const root = await mkdtemp(join(tmpdir(), `${id}-`));
+    try {
+      console.log(`Building ${id}`);
+      const source = join(TEMPLATES_DIR, id, "program/lib.rs");
+      const programs = [await build(compiler, id, source, root)];
+      for (const fixture of fixtures) {
+        const fixtureId = `${id}-${basename(fixture, ".rs")}`;
+        programs.push(await build(compiler, fixtureId, join(fixturesDir, fixture), root));
+      }
+      await link(root, programs);
+      if (!runSuites(root, suites)) failed = true;
+    } catch (error) {
+      console.error(error instanceof Error ? error.message : error);
+      failed = true;
+    } finally {
+      await rm(root, { recursive: true, force: true });
+    }
+  }
+
+  process.exitCode = failed ? 1 : 0;
+}
+
+main();
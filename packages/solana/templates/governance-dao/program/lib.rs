@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 declare_id!("DaoGov1111111111111111111111111111111");
 
@@ -6,57 +8,404 @@ declare_id!("DaoGov1111111111111111111111111111111");
 pub mod governance_dao {
     use super::*;
 
-    pub fn initialize_dao(ctx: Context<InitializeDao>, quorum: u64) -> Result<()> {
-        require!(quorum > 0, ErrorCode::InvalidQuorum);
+    pub fn initialize_dao(
+        ctx: Context<InitializeDao>,
+        quorum_bps: u16,
+        pass_threshold_bps: u16,
+        voting_period: i64,
+        timelock_delay: i64,
+        execution_window: i64,
+    ) -> Result<()> {
+        require!(quorum_bps > 0 && quorum_bps <= 10_000, ErrorCode::InvalidQuorum);
+        require!(
+            pass_threshold_bps > 0 && pass_threshold_bps <= 10_000,
+            ErrorCode::InvalidPassThreshold
+        );
+        require!(
+            voting_period > 0 && timelock_delay >= 0 && execution_window > 0,
+            ErrorCode::InvalidDuration
+        );
         let dao = &mut ctx.accounts.dao;
         dao.authority = ctx.accounts.authority.key();
-        dao.quorum = quorum;
+        dao.governance_mint = ctx.accounts.governance_mint.key();
+        dao.quorum_bps = quorum_bps;
+        dao.pass_threshold_bps = pass_threshold_bps;
+        dao.voting_period = voting_period;
+        dao.timelock_delay = timelock_delay;
+        dao.execution_window = execution_window;
         dao.proposal_count = 0;
+        dao.member_count = 1;
+        // No cap until governance sets one with set_membership_rules.
+        dao.max_members = u32::MAX;
         dao.bump = ctx.bumps.dao;
+        dao.treasury_bump = ctx.bumps.treasury;
+
+        let member = &mut ctx.accounts.member;
+        member.dao = dao.key();
+        member.wallet = ctx.accounts.authority.key();
+        member.bump = ctx.bumps.member;
         msg!("DAO initialized");
         Ok(())
     }
 
-    pub fn create_proposal(ctx: Context<CreateProposal>, proposal_id: u64) -> Result<()> {
+    pub fn set_quorum(ctx: Context<UpdateDaoConfig>, quorum_bps: u16) -> Result<()> {
+        require!(quorum_bps > 0 && quorum_bps <= 10_000, ErrorCode::InvalidQuorum);
+        // Proposals already created keep the quorum they snapshotted.
+        ctx.accounts.dao.quorum_bps = quorum_bps;
+        msg!("Quorum set to {} bps", quorum_bps);
+        Ok(())
+    }
+
+    pub fn set_voting_period(ctx: Context<UpdateDaoConfig>, voting_period: i64) -> Result<()> {
+        require!(voting_period > 0, ErrorCode::InvalidDuration);
+        ctx.accounts.dao.voting_period = voting_period;
+        msg!("Voting period set to {} seconds", voting_period);
+        Ok(())
+    }
+
+    pub fn set_membership_rules(ctx: Context<UpdateDaoConfig>, max_members: u32) -> Result<()> {
+        let dao = &mut ctx.accounts.dao;
+        require!(max_members >= dao.member_count, ErrorCode::InvalidMemberLimit);
+        dao.max_members = max_members;
+        msg!("Member limit set to {}", max_members);
+        Ok(())
+    }
+
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        proposal_id: u64,
+        action: ProposalAction,
+        instructions: Vec<ProposalInstruction>,
+        mode: VoteMode,
+    ) -> Result<()> {
         let dao = &mut ctx.accounts.dao;
         require!(proposal_id == dao.proposal_count, ErrorCode::InvalidProposalId);
+        require!(
+            action == ProposalAction::None || instructions.is_empty(),
+            ErrorCode::InvalidProposalAction
+        );
+        if let VoteMode::MultiOption { options } = mode {
+            require!(
+                options >= 2 && options as usize <= Proposal::MAX_OPTIONS,
+                ErrorCode::InvalidVoteMode
+            );
+            // Options have no single action to run, so multi-option proposals only signal.
+            require!(
+                action == ProposalAction::None && instructions.is_empty(),
+                ErrorCode::InvalidProposalAction
+            );
+        }
         let proposal = &mut ctx.accounts.proposal;
         proposal.dao = dao.key();
+        proposal.proposer = ctx.accounts.creator.key();
         proposal.id = proposal_id;
-        proposal.yes = 0;
-        proposal.no = 0;
-        proposal.executed = false;
+        proposal.state = ProposalState::Draft;
+        proposal.action = action;
+        proposal.mode = mode;
+        proposal.tally = VoteTally::new(&mode);
+        proposal.instructions = instructions;
+        // Snapshot the quorum so minting or burning mid-vote cannot move the bar. It stays in
+        // token units for every mode; quadratic tallies compare it against credits spent.
+        let quorum_votes = (ctx.accounts.governance_mint.supply as u128)
+            .checked_mul(dao.quorum_bps as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            / 10_000;
+        let quorum_votes = u64::try_from(quorum_votes).map_err(|_| ErrorCode::MathOverflow)?;
+        proposal.quorum_votes = quorum_votes.max(1);
+        proposal.voting_starts_at = 0;
+        proposal.voting_ends_at = 0;
+        proposal.executable_at = 0;
         dao.proposal_count = dao.proposal_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
         msg!("Proposal {} created", proposal_id);
         Ok(())
     }
 
-    pub fn cast_vote(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+    pub fn activate_proposal(ctx: Context<ProposerAction>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.state == ProposalState::Draft, ErrorCode::InvalidProposalState);
+        let now = Clock::get()?.unix_timestamp;
+        proposal.voting_starts_at = now;
+        proposal.voting_ends_at = now
+            .checked_add(ctx.accounts.dao.voting_period)
+            .ok_or(ErrorCode::MathOverflow)?;
+        proposal.state = ProposalState::Active;
+        msg!("Voting open until {}", proposal.voting_ends_at);
+        Ok(())
+    }
+
+    pub fn cancel_proposal(ctx: Context<ProposerAction>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            matches!(proposal.state, ProposalState::Draft | ProposalState::Active),
+            ErrorCode::InvalidProposalState
+        );
+        proposal.state = ProposalState::Cancelled;
+        msg!("Proposal cancelled");
+        Ok(())
+    }
+
+    pub fn finalize_proposal(ctx: Context<UpdateProposal>) -> Result<()> {
+        let dao = &ctx.accounts.dao;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.state == ProposalState::Active, ErrorCode::InvalidProposalState);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= proposal.voting_ends_at, ErrorCode::VotingStillOpen);
+
+        let passed = proposal.tally.get().passed(proposal.quorum_votes, dao.pass_threshold_bps);
+        proposal.state = if passed { ProposalState::Succeeded } else { ProposalState::Defeated };
+        msg!("Proposal finalized, passed: {}", passed);
+        Ok(())
+    }
+
+    pub fn queue_proposal(ctx: Context<UpdateProposal>) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
-        require!(!proposal.executed, ErrorCode::AlreadyExecuted);
+        require!(proposal.state == ProposalState::Succeeded, ErrorCode::InvalidProposalState);
+        let now = Clock::get()?.unix_timestamp;
+        proposal.executable_at = now
+            .checked_add(ctx.accounts.dao.timelock_delay)
+            .ok_or(ErrorCode::MathOverflow)?;
+        proposal.state = ProposalState::Queued;
+        msg!("Proposal executable at {}", proposal.executable_at);
+        Ok(())
+    }
+
+    pub fn expire_proposal(ctx: Context<UpdateProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.state == ProposalState::Queued, ErrorCode::InvalidProposalState);
+        let now = Clock::get()?.unix_timestamp;
+        let deadline = proposal
+            .executable_at
+            .checked_add(ctx.accounts.dao.execution_window)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(now >= deadline, ErrorCode::ExecutionWindowOpen);
+        proposal.state = ProposalState::Expired;
+        msg!("Proposal expired");
+        Ok(())
+    }
+
+    pub fn create_deposit(ctx: Context<CreateDeposit>) -> Result<()> {
+        let deposit = &mut ctx.accounts.deposit;
+        deposit.dao = ctx.accounts.dao.key();
+        deposit.voter = ctx.accounts.voter.key();
+        deposit.amount = 0;
+        deposit.active_votes = 0;
+        deposit.credits_spent = 0;
+        deposit.delegate = None;
+        deposit.bump = ctx.bumps.deposit;
+        msg!("Deposit created");
+        Ok(())
+    }
+
+    pub fn deposit_tokens(ctx: Context<DepositTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.voter_token_account.to_account_info(),
+            to: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.voter.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )?;
+
+        let deposit = &mut ctx.accounts.deposit;
+        deposit.amount = deposit.amount.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        msg!("Deposited {} governance tokens", amount);
+        Ok(())
+    }
+
+    pub fn withdraw_tokens(ctx: Context<WithdrawTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let deposit = &mut ctx.accounts.deposit;
+        require!(deposit.active_votes == 0, ErrorCode::TokensLocked);
+        deposit.amount = deposit.amount.checked_sub(amount).ok_or(ErrorCode::InsufficientDeposit)?;
+
+        // The vault owns itself, so proposals signed by the DAO PDA cannot move deposits.
+        let dao_key = ctx.accounts.dao.key();
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", dao_key.as_ref(), &[ctx.bumps.vault_token_account]]];
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.voter_token_account.to_account_info(),
+            authority: ctx.accounts.vault_token_account.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        msg!("Withdrew {} governance tokens", amount);
+        Ok(())
+    }
+
+    pub fn delegate_votes(ctx: Context<UpdateDelegation>, delegate: Pubkey) -> Result<()> {
+        require!(delegate != ctx.accounts.voter.key(), ErrorCode::InvalidDelegate);
+        ctx.accounts.deposit.delegate = Some(delegate);
+        msg!("Voting power delegated to {}", delegate);
+        Ok(())
+    }
+
+    pub fn revoke_delegation(ctx: Context<UpdateDelegation>) -> Result<()> {
+        // Votes the delegate already cast stay counted until the owner overrides them.
+        ctx.accounts.deposit.delegate = None;
+        msg!("Delegation revoked");
+        Ok(())
+    }
+
+    pub fn cast_vote(ctx: Context<CastVote>, choice: VoteChoice) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.assert_voting_open(Clock::get()?.unix_timestamp)?;
+        let voter = ctx.accounts.voter.key();
         let record = &mut ctx.accounts.vote_record;
-        require!(!record.voted, ErrorCode::AlreadyVoted);
+        // A delegate's vote on this power may be replaced by the owner, but only once.
+        require!(!record.voted || record.cast_by != voter, ErrorCode::AlreadyVoted);
+        let deposit = &mut ctx.accounts.deposit;
+        require!(deposit.amount > 0, ErrorCode::NoVotingPower);
 
-        if approve {
-            proposal.yes = proposal.yes.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        let tally = proposal.tally.get_mut();
+        if record.voted {
+            // The deposit is already locked by the delegate's vote; hand its credits back.
+            tally.remove(&record.choice, &record.ballot)?;
+            deposit.refund_credits(record.ballot.credits)?;
         } else {
-            proposal.no = proposal.no.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+            // Locks the deposit until this vote is relinquished.
+            deposit.active_votes =
+                deposit.active_votes.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        }
+        let ballot = tally.ballot(&choice, deposit)?;
+        tally.add(&choice, &ballot)?;
+        deposit.spend_credits(ballot.credits)?;
+
+        record.voter = voter;
+        record.cast_by = voter;
+        record.proposal = proposal.key();
+        record.voted = true;
+        record.choice = choice;
+        record.ballot = ballot;
+        msg!("Vote recorded with weight {}", ballot.weight);
+        Ok(())
+    }
+
+    pub fn cast_delegated_vote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CastDelegatedVote<'info>>,
+        choice: VoteChoice,
+    ) -> Result<()> {
+        // Delegations chain: if A delegates to B and B to C, C votes A's power by passing
+        // B's deposit as a remaining account. Each hop must name the next voter as delegate.
+        require!(
+            ctx.remaining_accounts.len() < VoterDeposit::MAX_DELEGATION_HOPS,
+            ErrorCode::DelegationChainTooLong
+        );
+        let dao_key = ctx.accounts.dao.key();
+        let mut next = ctx.accounts.deposit.delegate;
+        for info in ctx.remaining_accounts.iter() {
+            let hop: Account<VoterDeposit> = Account::try_from(info)?;
+            require!(hop.dao == dao_key && next == Some(hop.voter), ErrorCode::NotDelegate);
+            next = hop.delegate;
         }
+        require!(next == Some(ctx.accounts.delegate.key()), ErrorCode::NotDelegate);
 
-        record.voter = ctx.accounts.voter.key();
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.assert_voting_open(Clock::get()?.unix_timestamp)?;
+        let record = &mut ctx.accounts.vote_record;
+        require!(!record.voted, ErrorCode::AlreadyVoted);
+        let deposit = &mut ctx.accounts.deposit;
+        require!(deposit.amount > 0, ErrorCode::NoVotingPower);
+        let tally = proposal.tally.get_mut();
+        let ballot = tally.ballot(&choice, deposit)?;
+
+        tally.add(&choice, &ballot)?;
+        deposit.spend_credits(ballot.credits)?;
+        deposit.active_votes = deposit.active_votes.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+        // The record is keyed by the delegator, so the same power can only be counted once.
+        record.voter = ctx.accounts.delegator.key();
+        record.cast_by = ctx.accounts.delegate.key();
         record.proposal = proposal.key();
         record.voted = true;
-        msg!("Vote recorded");
+        record.choice = choice;
+        record.ballot = ballot;
+        msg!("Delegated vote recorded with weight {}", ballot.weight);
         Ok(())
     }
 
-    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+    pub fn relinquish_vote(ctx: Context<RelinquishVote>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let record = &ctx.accounts.vote_record;
+        // Votes on open proposals are withdrawn from the tally; closed ones only unlock.
+        let now = Clock::get()?.unix_timestamp;
+        if proposal.state == ProposalState::Active && now < proposal.voting_ends_at {
+            proposal.tally.get_mut().remove(&record.choice, &record.ballot)?;
+        }
+
+        let deposit = &mut ctx.accounts.deposit;
+        deposit.refund_credits(record.ballot.credits)?;
+        deposit.active_votes = deposit.active_votes.checked_sub(1).ok_or(ErrorCode::MathOverflow)?;
+        msg!("Vote relinquished");
+        Ok(())
+    }
+
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
         let dao = &ctx.accounts.dao;
         let proposal = &mut ctx.accounts.proposal;
-        require!(!proposal.executed, ErrorCode::AlreadyExecuted);
-        require!(proposal.yes >= dao.quorum, ErrorCode::NotEnoughVotes);
-        proposal.executed = true;
-        msg!("Proposal executed");
+        proposal.assert_executable(Clock::get()?.unix_timestamp, dao.execution_window)?;
+        require!(proposal.action == ProposalAction::None, ErrorCode::InvalidProposalAction);
+        // Anchor writes accounts back only on exit, so persist Executed before any CPI:
+        // a re-entrant execute_proposal reads the account data, not this in-memory copy.
+        proposal.state = ProposalState::Executed;
+        proposal.exit(&crate::ID)?;
+
+        let dao_key = dao.key();
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[b"dao", dao.authority.as_ref(), &[dao.bump]],
+            &[b"treasury", dao_key.as_ref(), &[dao.treasury_bump]],
+        ];
+
+        for stored in proposal.instructions.iter() {
+            let (ix, infos) = stored.resolve(ctx.remaining_accounts)?;
+            invoke_signed(&ix, &infos, signer_seeds)?;
+        }
+        msg!("Proposal executed with {} instructions", proposal.instructions.len());
+        Ok(())
+    }
+
+    pub fn execute_add_member(ctx: Context<ExecuteAddMember>) -> Result<()> {
+        let dao = &mut ctx.accounts.dao;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.assert_executable(Clock::get()?.unix_timestamp, dao.execution_window)?;
+        require!(
+            proposal.action == ProposalAction::AddMember { wallet: ctx.accounts.wallet.key() },
+            ErrorCode::InvalidProposalAction
+        );
+
+        require!(dao.member_count < dao.max_members, ErrorCode::MemberLimitReached);
+        dao.member_count = dao.member_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+        let member = &mut ctx.accounts.member;
+        member.dao = dao.key();
+        member.wallet = ctx.accounts.wallet.key();
+        member.bump = ctx.bumps.member;
+        proposal.state = ProposalState::Executed;
+        msg!("Member {} added", member.wallet);
+        Ok(())
+    }
+
+    pub fn execute_remove_member(ctx: Context<ExecuteRemoveMember>) -> Result<()> {
+        let dao = &mut ctx.accounts.dao;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.assert_executable(Clock::get()?.unix_timestamp, dao.execution_window)?;
+        require!(
+            proposal.action == ProposalAction::RemoveMember { wallet: ctx.accounts.member.wallet },
+            ErrorCode::InvalidProposalAction
+        );
+        dao.member_count = dao.member_count.checked_sub(1).ok_or(ErrorCode::MathOverflow)?;
+        proposal.state = ProposalState::Executed;
+        msg!("Member {} removed", ctx.accounts.member.wallet);
         Ok(())
     }
 }
@@ -71,24 +420,65 @@ pub struct InitializeDao<'info> {
         bump
     )]
     pub dao: Account<'info, Dao>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Member::LEN,
+        seeds = [b"member", dao.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    pub governance_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", dao.key().as_ref()],
+        bump,
+        token::mint = governance_mint,
+        token::authority = vault_token_account
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(seeds = [b"treasury", dao.key().as_ref()], bump)]
+    pub treasury: SystemAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDaoConfig<'info> {
+    // The DAO PDA can only sign through execute_proposal, so the authority key has no say here.
+    #[account(
+        mut,
+        signer @ ErrorCode::NotGovernance,
+        seeds = [b"dao", dao.authority.as_ref()],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
 }
 
 #[derive(Accounts)]
-#[instruction(proposal_id: u64)]
+#[instruction(
+    proposal_id: u64,
+    action: ProposalAction,
+    instructions: Vec<ProposalInstruction>,
+    mode: VoteMode
+)]
 pub struct CreateProposal<'info> {
     #[account(mut, seeds = [b"dao", authority.key().as_ref()], bump = dao.bump)]
     pub dao: Account<'info, Dao>,
     #[account(
         init,
         payer = creator,
-        space = 8 + Proposal::LEN,
+        space = 8 + Proposal::space(&mode, &instructions),
         seeds = [b"proposal", dao.key().as_ref(), proposal_id.to_le_bytes().as_ref()],
         bump
     )]
     pub proposal: Account<'info, Proposal>,
+    #[account(address = dao.governance_mint @ ErrorCode::InvalidGovernanceMint)]
+    pub governance_mint: Account<'info, Mint>,
     #[account(mut)]
     pub creator: Signer<'info>,
     /// CHECK: Only used as seed reference.
@@ -97,13 +487,98 @@ pub struct CreateProposal<'info> {
 }
 
 #[derive(Accounts)]
-pub struct CastVote<'info> {
+pub struct ProposerAction<'info> {
     pub dao: Account<'info, Dao>,
-    #[account(mut)]
+    #[account(
+        mut,
+        has_one = dao @ ErrorCode::DaoMismatch,
+        has_one = proposer @ ErrorCode::NotProposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProposal<'info> {
+    pub dao: Account<'info, Dao>,
+    #[account(mut, has_one = dao @ ErrorCode::DaoMismatch)]
     pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDeposit<'info> {
+    pub dao: Account<'info, Dao>,
     #[account(
         init,
         payer = voter,
+        space = 8 + VoterDeposit::LEN,
+        seeds = [b"deposit", dao.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub deposit: Account<'info, VoterDeposit>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositTokens<'info> {
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        seeds = [b"deposit", dao.key().as_ref(), voter.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, VoterDeposit>,
+    #[account(mut, seeds = [b"vault", dao.key().as_ref()], bump)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = dao.governance_mint,
+        token::authority = voter
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+    pub voter: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTokens<'info> {
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        seeds = [b"deposit", dao.key().as_ref(), voter.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, VoterDeposit>,
+    #[account(mut, seeds = [b"vault", dao.key().as_ref()], bump)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = dao.governance_mint)]
+    pub voter_token_account: Account<'info, TokenAccount>,
+    pub voter: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    pub dao: Account<'info, Dao>,
+    #[account(mut, has_one = dao @ ErrorCode::DaoMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"member", dao.key().as_ref(), voter.key().as_ref()],
+        bump = member.bump,
+        has_one = dao @ ErrorCode::DaoMismatch
+    )]
+    pub member: Account<'info, Member>,
+    #[account(
+        mut,
+        seeds = [b"deposit", dao.key().as_ref(), voter.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, VoterDeposit>,
+    #[account(
+        init_if_needed,
+        payer = voter,
         space = 8 + VoteRecord::LEN,
         seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
         bump
@@ -114,52 +589,600 @@ pub struct CastVote<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateDelegation<'info> {
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        seeds = [b"deposit", dao.key().as_ref(), voter.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, VoterDeposit>,
+    pub voter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CastDelegatedVote<'info> {
+    pub dao: Account<'info, Dao>,
+    #[account(mut, has_one = dao @ ErrorCode::DaoMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"member", dao.key().as_ref(), delegator.key().as_ref()],
+        bump = member.bump,
+        has_one = dao @ ErrorCode::DaoMismatch
+    )]
+    pub member: Account<'info, Member>,
+    #[account(
+        mut,
+        seeds = [b"deposit", dao.key().as_ref(), delegator.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, VoterDeposit>,
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + VoteRecord::LEN,
+        seeds = [b"vote", proposal.key().as_ref(), delegator.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    /// CHECK: Owner of the delegated deposit, bound by the member and deposit seeds.
+    pub delegator: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RelinquishVote<'info> {
+    pub dao: Account<'info, Dao>,
+    #[account(mut, has_one = dao @ ErrorCode::DaoMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds = [b"deposit", dao.key().as_ref(), voter.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, VoterDeposit>,
+    #[account(
+        mut,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump,
+        has_one = voter,
+        has_one = proposal,
+        close = voter
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ExecuteProposal<'info> {
     pub dao: Account<'info, Dao>,
+    #[account(mut, has_one = dao @ ErrorCode::DaoMismatch)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAddMember<'info> {
     #[account(mut)]
+    pub dao: Account<'info, Dao>,
+    #[account(mut, has_one = dao @ ErrorCode::DaoMismatch)]
     pub proposal: Account<'info, Proposal>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Member::LEN,
+        seeds = [b"member", dao.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    /// CHECK: Must match the wallet named in the proposal action.
+    pub wallet: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRemoveMember<'info> {
+    #[account(mut)]
+    pub dao: Account<'info, Dao>,
+    #[account(mut, has_one = dao @ ErrorCode::DaoMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds = [b"member", dao.key().as_ref(), member.wallet.as_ref()],
+        bump = member.bump,
+        has_one = dao @ ErrorCode::DaoMismatch,
+        close = rent_receiver
+    )]
+    pub member: Account<'info, Member>,
+    /// CHECK: The removed member's wallet, which gets the member account rent back.
+    #[account(mut, address = member.wallet @ ErrorCode::InvalidRentReceiver)]
+    pub rent_receiver: UncheckedAccount<'info>,
 }
 
 #[account]
 pub struct Dao {
     pub authority: Pubkey,
-    pub quorum: u64,
+    pub governance_mint: Pubkey,
+    pub quorum_bps: u16,
+    pub pass_threshold_bps: u16,
+    pub voting_period: i64,
+    pub timelock_delay: i64,
+    pub execution_window: i64,
     pub proposal_count: u64,
+    pub member_count: u32,
+    pub max_members: u32,
     pub bump: u8,
+    pub treasury_bump: u8,
 }
 
 impl Dao {
-    pub const LEN: usize = 32 + 8 + 8 + 1;
+    pub const LEN: usize = 32 + 32 + 2 + 2 + 8 + 8 + 8 + 8 + 4 + 4 + 1 + 1;
 }
 
 #[account]
 pub struct Proposal {
     pub dao: Pubkey,
+    pub proposer: Pubkey,
     pub id: u64,
+    pub state: ProposalState,
+    pub action: ProposalAction,
+    pub mode: VoteMode,
+    pub quorum_votes: u64,
+    pub voting_starts_at: i64,
+    pub voting_ends_at: i64,
+    pub executable_at: i64,
+    pub tally: VoteTally,
+    pub instructions: Vec<ProposalInstruction>,
+}
+
+impl Proposal {
+    pub const MAX_OPTIONS: usize = 8;
+    pub const BASE_LEN: usize =
+        32 + 32 + 8 + 1 + ProposalAction::LEN + VoteMode::LEN + 8 + 8 + 8 + 8 + 4;
+
+    pub fn assert_executable(&self, now: i64, execution_window: i64) -> Result<()> {
+        require!(self.state != ProposalState::Executed, ErrorCode::AlreadyExecuted);
+        require!(self.state != ProposalState::Defeated, ErrorCode::NotEnoughVotes);
+        require!(self.state == ProposalState::Queued, ErrorCode::InvalidProposalState);
+        require!(now >= self.executable_at, ErrorCode::TimelockActive);
+        let deadline = self
+            .executable_at
+            .checked_add(execution_window)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(now < deadline, ErrorCode::ProposalExpired);
+        Ok(())
+    }
+
+    pub fn assert_voting_open(&self, now: i64) -> Result<()> {
+        require!(self.state == ProposalState::Active, ErrorCode::InvalidProposalState);
+        require!(
+            now >= self.voting_starts_at && now < self.voting_ends_at,
+            ErrorCode::VotingClosed
+        );
+        Ok(())
+    }
+
+    pub fn space(mode: &VoteMode, instructions: &[ProposalInstruction]) -> usize {
+        Self::BASE_LEN
+            + VoteTally::space(mode)
+            + instructions
+                .iter()
+                .map(ProposalInstruction::serialized_len)
+                .sum::<usize>()
+    }
+}
+
+// Counting rules for one voting mode. Handlers only talk to this trait, so a new mode is
+// a new implementation plus a `VoteTally` variant.
+pub trait Tally {
+    // Validates the choice for this mode and prices it against the voter's deposit.
+    fn ballot(&self, choice: &VoteChoice, deposit: &VoterDeposit) -> Result<Ballot>;
+    fn add(&mut self, choice: &VoteChoice, ballot: &Ballot) -> Result<()>;
+    fn remove(&mut self, choice: &VoteChoice, ballot: &Ballot) -> Result<()>;
+    // The leading choice must reach quorum and hold the threshold share of cast votes.
+    fn passed(&self, quorum: u64, pass_threshold_bps: u16) -> bool;
+}
+
+fn holds_threshold(lead: u64, total: u128, pass_threshold_bps: u16) -> bool {
+    (lead as u128) * 10_000 >= total * (pass_threshold_bps as u128)
+}
+
+fn add_to(tally: &mut u64, amount: u64) -> Result<()> {
+    *tally = tally.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+fn sub_from(tally: &mut u64, amount: u64) -> Result<()> {
+    *tally = tally.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+// What one vote counted for: `weight` in the tally and `credits` debited from the deposit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Ballot {
+    pub weight: u64,
+    pub credits: u64,
+}
+
+impl Ballot {
+    pub const LEN: usize = 8 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BinaryTally {
     pub yes: u64,
     pub no: u64,
-    pub executed: bool,
 }
 
-impl Proposal {
-    pub const LEN: usize = 32 + 8 + 8 + 8 + 1;
+impl BinaryTally {
+    pub const LEN: usize = 8 + 8;
+
+    fn side(&mut self, choice: &VoteChoice) -> Result<&mut u64> {
+        match choice {
+            VoteChoice::Yes => Ok(&mut self.yes),
+            VoteChoice::No => Ok(&mut self.no),
+            _ => err!(ErrorCode::InvalidVoteChoice),
+        }
+    }
+}
+
+impl Tally for BinaryTally {
+    fn ballot(&self, choice: &VoteChoice, deposit: &VoterDeposit) -> Result<Ballot> {
+        require!(matches!(choice, VoteChoice::Yes | VoteChoice::No), ErrorCode::InvalidVoteChoice);
+        Ok(Ballot { weight: deposit.amount, credits: 0 })
+    }
+
+    fn add(&mut self, choice: &VoteChoice, ballot: &Ballot) -> Result<()> {
+        add_to(self.side(choice)?, ballot.weight)
+    }
+
+    fn remove(&mut self, choice: &VoteChoice, ballot: &Ballot) -> Result<()> {
+        sub_from(self.side(choice)?, ballot.weight)
+    }
+
+    fn passed(&self, quorum: u64, pass_threshold_bps: u16) -> bool {
+        let total = (self.yes as u128) + (self.no as u128);
+        self.yes >= quorum && holds_threshold(self.yes, total, pass_threshold_bps)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OptionTally {
+    pub votes: Vec<u64>,
+}
+
+impl OptionTally {
+    fn option(&mut self, choice: &VoteChoice) -> Result<&mut u64> {
+        match choice {
+            VoteChoice::Option { index } => self
+                .votes
+                .get_mut(*index as usize)
+                .ok_or_else(|| error!(ErrorCode::InvalidVoteChoice)),
+            _ => err!(ErrorCode::InvalidVoteChoice),
+        }
+    }
+}
+
+impl Tally for OptionTally {
+    fn ballot(&self, choice: &VoteChoice, deposit: &VoterDeposit) -> Result<Ballot> {
+        match choice {
+            VoteChoice::Option { index } if (*index as usize) < self.votes.len() => {
+                Ok(Ballot { weight: deposit.amount, credits: 0 })
+            }
+            _ => err!(ErrorCode::InvalidVoteChoice),
+        }
+    }
+
+    fn add(&mut self, choice: &VoteChoice, ballot: &Ballot) -> Result<()> {
+        add_to(self.option(choice)?, ballot.weight)
+    }
+
+    fn remove(&mut self, choice: &VoteChoice, ballot: &Ballot) -> Result<()> {
+        sub_from(self.option(choice)?, ballot.weight)
+    }
+
+    fn passed(&self, quorum: u64, pass_threshold_bps: u16) -> bool {
+        let lead = self.votes.iter().copied().max().unwrap_or(0);
+        let total = self.votes.iter().map(|votes| *votes as u128).sum::<u128>();
+        lead >= quorum && holds_threshold(lead, total, pass_threshold_bps)
+    }
+}
+
+// Weights are square roots of the credits spent; credits are kept per side so quorum can
+// be measured in token units like the other modes.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct QuadraticTally {
+    pub yes: u64,
+    pub no: u64,
+    pub yes_credits: u64,
+    pub no_credits: u64,
+}
+
+impl QuadraticTally {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+
+    fn side(&mut self, choice: &VoteChoice) -> Result<(&mut u64, &mut u64)> {
+        match choice {
+            VoteChoice::Quadratic { approve: true, .. } => {
+                Ok((&mut self.yes, &mut self.yes_credits))
+            }
+            VoteChoice::Quadratic { approve: false, .. } => {
+                Ok((&mut self.no, &mut self.no_credits))
+            }
+            _ => err!(ErrorCode::InvalidVoteChoice),
+        }
+    }
+}
+
+impl Tally for QuadraticTally {
+    fn ballot(&self, choice: &VoteChoice, deposit: &VoterDeposit) -> Result<Ballot> {
+        let credits = match choice {
+            VoteChoice::Quadratic { credits, .. } => *credits,
+            _ => return err!(ErrorCode::InvalidVoteChoice),
+        };
+        require!(
+            credits > 0 && credits <= deposit.unspent_credits(),
+            ErrorCode::InsufficientCredits
+        );
+        Ok(Ballot { weight: integer_sqrt(credits), credits })
+    }
+
+    fn add(&mut self, choice: &VoteChoice, ballot: &Ballot) -> Result<()> {
+        let (weight, credits) = self.side(choice)?;
+        add_to(weight, ballot.weight)?;
+        add_to(credits, ballot.credits)
+    }
+
+    fn remove(&mut self, choice: &VoteChoice, ballot: &Ballot) -> Result<()> {
+        let (weight, credits) = self.side(choice)?;
+        sub_from(weight, ballot.weight)?;
+        sub_from(credits, ballot.credits)
+    }
+
+    fn passed(&self, quorum: u64, pass_threshold_bps: u16) -> bool {
+        let total = (self.yes as u128) + (self.no as u128);
+        self.yes_credits >= quorum && holds_threshold(self.yes, total, pass_threshold_bps)
+    }
+}
+
+// Per-mode tally storage on a proposal; the variant always matches `Proposal::mode`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum VoteTally {
+    Binary(BinaryTally),
+    MultiOption(OptionTally),
+    Quadratic(QuadraticTally),
+}
+
+impl VoteTally {
+    pub fn new(mode: &VoteMode) -> Self {
+        match mode {
+            VoteMode::Binary => VoteTally::Binary(BinaryTally { yes: 0, no: 0 }),
+            VoteMode::MultiOption { .. } => VoteTally::MultiOption(OptionTally {
+                votes: vec![0; mode.option_count()],
+            }),
+            VoteMode::Quadratic => VoteTally::Quadratic(QuadraticTally {
+                yes: 0,
+                no: 0,
+                yes_credits: 0,
+                no_credits: 0,
+            }),
+        }
+    }
+
+    pub fn space(mode: &VoteMode) -> usize {
+        1 + match mode {
+            VoteMode::Binary => BinaryTally::LEN,
+            VoteMode::MultiOption { .. } => 4 + mode.option_count() * 8,
+            VoteMode::Quadratic => QuadraticTally::LEN,
+        }
+    }
+
+    pub fn get(&self) -> &dyn Tally {
+        match self {
+            VoteTally::Binary(tally) => tally,
+            VoteTally::MultiOption(tally) => tally,
+            VoteTally::Quadratic(tally) => tally,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut dyn Tally {
+        match self {
+            VoteTally::Binary(tally) => tally,
+            VoteTally::MultiOption(tally) => tally,
+            VoteTally::Quadratic(tally) => tally,
+        }
+    }
+}
+
+fn integer_sqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+    // Newton's method from an upper bound converges down to floor(sqrt(value)).
+    let mut x = value / 2 + 1;
+    let mut y = (x + value / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+impl ProposalInstruction {
+    pub fn serialized_len(&self) -> usize {
+        32 + 4 + self.accounts.len() * ProposalAccountMeta::LEN + 4 + self.data.len()
+    }
+
+    // Looks up each stored key in the accounts passed to execute_proposal; the program
+    // account goes last, after the instruction's own accounts, as invoke_signed expects.
+    pub fn resolve<'info>(
+        &self,
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<(Instruction, Vec<AccountInfo<'info>>)> {
+        let lookup = |key: &Pubkey| {
+            remaining
+                .iter()
+                .find(|account| account.key == key)
+                .ok_or(ErrorCode::MissingInstructionAccount)
+        };
+        let mut metas = Vec::with_capacity(self.accounts.len());
+        let mut infos = Vec::with_capacity(self.accounts.len() + 1);
+        for meta in self.accounts.iter() {
+            let info = lookup(&meta.pubkey)?;
+            require!(!meta.is_writable || info.is_writable, ErrorCode::AccountMetaMismatch);
+            metas.push(AccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            });
+            infos.push(info.clone());
+        }
+        let program = lookup(&self.program_id)?;
+        require!(program.executable, ErrorCode::AccountMetaMismatch);
+        infos.push(program.clone());
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data: self.data.clone(),
+        };
+        Ok((ix, infos))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ProposalAccountMeta {
+    pub const LEN: usize = 32 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalState {
+    Draft,
+    Active,
+    Succeeded,
+    Defeated,
+    Queued,
+    Executed,
+    Cancelled,
+    Expired,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalAction {
+    None,
+    AddMember { wallet: Pubkey },
+    RemoveMember { wallet: Pubkey },
+}
+
+impl ProposalAction {
+    pub const LEN: usize = 1 + 32;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VoteMode {
+    Binary,
+    MultiOption { options: u8 },
+    Quadratic,
+}
+
+impl VoteMode {
+    pub const LEN: usize = 1 + 1;
+
+    pub fn option_count(&self) -> usize {
+        match self {
+            VoteMode::MultiOption { options } => *options as usize,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VoteChoice {
+    Yes,
+    No,
+    Option { index: u8 },
+    Quadratic { approve: bool, credits: u64 },
+}
+
+impl VoteChoice {
+    pub const LEN: usize = 1 + 1 + 8;
+}
+
+#[account]
+pub struct Member {
+    pub dao: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+impl Member {
+    pub const LEN: usize = 32 + 32 + 1;
+}
+
+#[account]
+pub struct VoterDeposit {
+    pub dao: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub active_votes: u32,
+    // Quadratic credits locked in votes that have not been relinquished yet.
+    pub credits_spent: u64,
+    pub delegate: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl VoterDeposit {
+    pub const LEN: usize = 32 + 32 + 8 + 4 + 8 + (1 + 32) + 1;
+    pub const MAX_DELEGATION_HOPS: usize = 4;
+
+    pub fn unspent_credits(&self) -> u64 {
+        self.amount.saturating_sub(self.credits_spent)
+    }
+
+    pub fn spend_credits(&mut self, credits: u64) -> Result<()> {
+        add_to(&mut self.credits_spent, credits)
+    }
+
+    pub fn refund_credits(&mut self, credits: u64) -> Result<()> {
+        sub_from(&mut self.credits_spent, credits)
+    }
 }
 
 #[account]
 pub struct VoteRecord {
     pub voter: Pubkey,
+    pub cast_by: Pubkey,
     pub proposal: Pubkey,
     pub voted: bool,
+    pub choice: VoteChoice,
+    pub ballot: Ballot,
 }
 
 impl VoteRecord {
-    pub const LEN: usize = 32 + 32 + 1;
+    pub const LEN: usize = 32 + 32 + 32 + 1 + VoteChoice::LEN + Ballot::LEN;
 }
 
 #[error_code]
 pub enum ErrorCode {
-    #[msg("Quorum must be greater than zero")]
+    #[msg("Quorum must be between 1 and 10000 basis points")]
     InvalidQuorum,
     #[msg("Proposal id does not match the next counter")]
     InvalidProposalId,
@@ -171,4 +1194,60 @@ pub enum ErrorCode {
     AlreadyVoted,
     #[msg("Not enough yes votes to execute")]
     NotEnoughVotes,
+    #[msg("Account belongs to a different DAO")]
+    DaoMismatch,
+    #[msg("Proposal action does not match this instruction")]
+    InvalidProposalAction,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Mint is not the DAO governance mint")]
+    InvalidGovernanceMint,
+    #[msg("Deposit has no voting power")]
+    NoVotingPower,
+    #[msg("Deposit is locked by active votes")]
+    TokensLocked,
+    #[msg("Withdrawal exceeds deposited amount")]
+    InsufficientDeposit,
+    #[msg("Instruction account missing from remaining accounts")]
+    MissingInstructionAccount,
+    #[msg("Supplied account does not match the stored meta")]
+    AccountMetaMismatch,
+    #[msg("Pass threshold must be between 1 and 10000 basis points")]
+    InvalidPassThreshold,
+    #[msg("Durations must be positive")]
+    InvalidDuration,
+    #[msg("Proposal is not in the required state")]
+    InvalidProposalState,
+    #[msg("Only the proposer can do this")]
+    NotProposer,
+    #[msg("Voting window is closed")]
+    VotingClosed,
+    #[msg("Voting window is still open")]
+    VotingStillOpen,
+    #[msg("Timelock has not elapsed")]
+    TimelockActive,
+    #[msg("Execution window has passed")]
+    ProposalExpired,
+    #[msg("Execution window is still open")]
+    ExecutionWindowOpen,
+    #[msg("Cannot delegate to yourself")]
+    InvalidDelegate,
+    #[msg("Signer is not the deposit delegate")]
+    NotDelegate,
+    #[msg("Delegation chain has too many hops")]
+    DelegationChainTooLong,
+    #[msg("Only an executed proposal of this DAO can change its configuration")]
+    NotGovernance,
+    #[msg("Member limit is below the current member count")]
+    InvalidMemberLimit,
+    #[msg("DAO has reached its member limit")]
+    MemberLimitReached,
+    #[msg("Multi-option proposals need between 2 and 8 options")]
+    InvalidVoteMode,
+    #[msg("Vote choice does not fit the proposal voting mode")]
+    InvalidVoteChoice,
+    #[msg("Quadratic credits must be positive and within the unspent deposit")]
+    InsufficientCredits,
+    #[msg("Member rent must go back to the removed member's wallet")]
+    InvalidRentReceiver,
 }
@@ -1,14 +1,42 @@
 const INSTRUCTIONS = [
-  { name: "initialize_dao", args: [{ name: "quorum", type: "u64" }] },
-  { name: "create_proposal", args: [{ name: "proposal_id", type: "u64" }] },
-  { name: "cast_vote", args: [{ name: "approve", type: "bool" }] },
+  {
+    name: "initialize_dao",
+    args: [
+      { name: "quorum_bps", type: "u16" },
+      { name: "pass_threshold_bps", type: "u16" },
+      { name: "voting_period", type: "i64" },
+      { name: "timelock_delay", type: "i64" },
+      { name: "execution_window", type: "i64" },
+    ],
+  },
+  { name: "set_quorum", args: [{ name: "quorum_bps", type: "u16" }] },
+  { name: "set_voting_period", args: [{ name: "voting_period", type: "i64" }] },
+  { name: "set_membership_rules", args: [{ name: "max_members", type: "u32" }] },
+  { name: "create_proposal", args: [{ name: "proposal_id", type: "u64" }, { name: "action", type: "defined:ProposalAction" }, { name: "instructions", type: "vec:defined:ProposalInstruction" }, { name: "mode", type: "defined:VoteMode" }] },
+  { name: "activate_proposal", args: [] },
+  { name: "cancel_proposal", args: [] },
+  { name: "finalize_proposal", args: [] },
+  { name: "queue_proposal", args: [] },
+  { name: "expire_proposal", args: [] },
+  { name: "create_deposit", args: [] },
+  { name: "deposit_tokens", args: [{ name: "amount", type: "u64" }] },
+  { name: "withdraw_tokens", args: [{ name: "amount", type: "u64" }] },
+  { name: "delegate_votes", args: [{ name: "delegate", type: "publicKey" }] },
+  { name: "revoke_delegation", args: [] },
+  { name: "cast_vote", args: [{ name: "choice", type: "defined:VoteChoice" }] },
+  { name: "cast_delegated_vote", args: [{ name: "choice", type: "defined:VoteChoice" }] },
+  { name: "relinquish_vote", args: [] },
   { name: "execute_proposal", args: [] },
+  { name: "execute_add_member", args: [] },
+  { name: "execute_remove_member", args: [] },
 ];
 
 function norm(name) { return String(name || "").replace(/[_-]/g, "").toLowerCase(); }
 function snakeToCamel(name) { return String(name).replace(/_([a-z])/g, function (_, c) { return c.toUpperCase(); }); }
-function typeToString(type) { if (typeof type === "string") return type; if (type && type.defined) return "defined:" + type.defined; return JSON.stringify(type); }
+function typeToString(type) { if (typeof type === "string") return type; if (type && type.defined) return "defined:" + type.defined; if (type && type.vec) return "vec:" + typeToString(type.vec); if (type && type.option) return "option:" + typeToString(type.option); return JSON.stringify(type); }
 function findInstruction(idl, name) { const xs = Array.isArray(idl && idl.instructions) ? idl.instructions : []; return xs.find(function (x) { return norm(x.name) === norm(name); }); }
+function accountNames(ix) { return (Array.isArray(ix && ix.accounts) ? ix.accounts : []).map(function (a) { return norm(a.name); }); }
+function findAccount(ix, name) { return (Array.isArray(ix && ix.accounts) ? ix.accounts : []).find(function (a) { return norm(a.name) === norm(name); }); }
 
 describe("governance-dao behavior", () => {
   it("injects playground runtime context", () => { expect(program).to.exist; expect(provider).to.exist; });
@@ -21,20 +49,136 @@ describe("governance-dao behavior", () => {
       expect(args.length).to.equal(e.args.length);
       e.args.forEach(function (arg, i) {
         expect(norm(args[i].name)).to.equal(norm(arg.name));
-        expect(typeToString(args[i].type)).to.equal(arg.type);
+        expect(typeToString(args[i].type).replace("pubkey", "publicKey")).to.equal(arg.type);
       });
     });
   });
 
   it("exposes proposal lifecycle builders", () => {
     const m = program.methods || {};
-    expect(m[snakeToCamel("initialize_dao")](1).rpc).to.be.a("function");
-    expect(m[snakeToCamel("create_proposal")](0).rpc).to.be.a("function");
-    expect(m[snakeToCamel("cast_vote")](true).rpc).to.be.a("function");
+    expect(m[snakeToCamel("initialize_dao")](1, 5000, 60, 0, 3600).rpc).to.be.a("function");
+    expect(m[snakeToCamel("create_proposal")](0, { none: {} }, [], { binary: {} }).rpc).to.be.a("function");
+    expect(m[snakeToCamel("set_quorum")](2500).rpc).to.be.a("function");
+    expect(m[snakeToCamel("set_voting_period")](120).rpc).to.be.a("function");
+    expect(m[snakeToCamel("set_membership_rules")](10).rpc).to.be.a("function");
+    ["activate_proposal", "cancel_proposal", "finalize_proposal", "queue_proposal", "expire_proposal"].forEach(function (name) {
+      expect(m[snakeToCamel(name)]().rpc).to.be.a("function");
+    });
+    expect(m[snakeToCamel("create_deposit")]().rpc).to.be.a("function");
+    expect(m[snakeToCamel("deposit_tokens")](1).rpc).to.be.a("function");
+    expect(m[snakeToCamel("withdraw_tokens")](1).rpc).to.be.a("function");
+    expect(m[snakeToCamel("cast_vote")]({ yes: {} }).rpc).to.be.a("function");
+    expect(m[snakeToCamel("delegate_votes")](provider.wallet.publicKey).rpc).to.be.a("function");
+    expect(m[snakeToCamel("revoke_delegation")]().rpc).to.be.a("function");
+    expect(m[snakeToCamel("cast_delegated_vote")]({ option: { index: 1 } }).rpc).to.be.a("function");
+    expect(m[snakeToCamel("relinquish_vote")]().rpc).to.be.a("function");
     expect(m[snakeToCamel("execute_proposal")]().rpc).to.be.a("function");
+    expect(m[snakeToCamel("execute_add_member")]().rpc).to.be.a("function");
+    expect(m[snakeToCamel("execute_remove_member")]().rpc).to.be.a("function");
+  });
+
+
+  it("weights votes by a deposit bound to the voter", () => {
+    const ix = findInstruction(program.idl, "cast_vote");
+    const deposit = findAccount(ix, "deposit");
+    expect(deposit, "cast_vote must read the voter deposit").to.exist;
+    expect(deposit.isMut || deposit.writable || false).to.equal(true);
+    if (deposit.pda) {
+      const seeds = deposit.pda.seeds.map(function (s) { return s.kind === "const" ? Buffer.from(s.value).toString() : norm(s.path); });
+      expect(seeds).to.deep.equal(["deposit", norm("dao"), norm("voter")]);
+    }
+  });
+
+
+  it("counts delegated power once per delegator", () => {
+    const ix = findInstruction(program.idl, "cast_delegated_vote");
+    expect(accountNames(ix)).to.include.members(["member", "deposit", "vote_record", "delegator", "delegate"].map(norm));
+    const delegate = findAccount(ix, "delegate");
+    const delegator = findAccount(ix, "delegator");
+    expect(delegate.isSigner || delegate.signer || false).to.equal(true);
+    expect(delegator.isSigner || delegator.signer || false).to.equal(false);
+    // Vote records are keyed by the owner of the power, so a direct vote and a delegated vote share one record.
+    const record = findAccount(ix, "vote_record");
+    if (record.pda) {
+      const seeds = record.pda.seeds.map(function (s) { return s.kind === "const" ? Buffer.from(s.value).toString() : norm(s.path); });
+      expect(seeds).to.deep.equal(["vote", norm("proposal"), norm("delegator")]);
+    }
+  });
+
+
+
+  it("routes every voting mode through one tally interface", () => {
+    const types = Array.isArray(program.idl && program.idl.types) ? program.idl.types : [];
+    const find = function (name) { return types.find(function (t) { return norm(t.name) === norm(name); }); };
+    expect(find("VoteMode").type.variants.map(function (v) { return norm(v.name); })).to.deep.equal(["Binary", "MultiOption", "Quadratic"].map(norm));
+    expect(find("VoteChoice").type.variants.map(function (v) { return norm(v.name); })).to.deep.equal(["Yes", "No", "Option", "Quadratic"].map(norm));
+    // Each mode stores its own tally; the Tally trait itself is not part of the IDL.
+    expect(find("VoteTally").type.variants.map(function (v) { return norm(v.name); })).to.deep.equal(["Binary", "MultiOption", "Quadratic"].map(norm));
+    expect(find("QuadraticTally").type.fields.map(function (f) { return norm(f.name); })).to.deep.equal(["yes", "no", "yes_credits", "no_credits"].map(norm));
+    expect(find("Ballot").type.fields.map(function (f) { return norm(f.name); })).to.deep.equal(["weight", "credits"].map(norm));
+    // Modes only change the choice argument, never the accounts cast_vote validates.
+    expect(accountNames(findInstruction(program.idl, "cast_vote"))).to.deep.equal(
+      ["dao", "proposal", "member", "deposit", "vote_record", "voter", "system_program"].map(norm)
+    );
+    const errors = (program.idl.errors || []).map(function (e) { return norm(e.name); });
+    expect(errors).to.include.members(["InvalidVoteMode", "InvalidVoteChoice", "InsufficientCredits"].map(norm));
   });
 
-  it("exposes Dao/Proposal/VoteRecord schemas", () => {
+  it("stores executable instructions on proposals", () => {
+    const types = Array.isArray(program.idl && program.idl.types) ? program.idl.types : [];
+    const find = function (name) { return types.find(function (t) { return norm(t.name) === norm(name); }); };
+    const ix = find("ProposalInstruction");
+    const meta = find("ProposalAccountMeta");
+    expect(ix, "Missing ProposalInstruction type").to.exist;
+    expect(meta, "Missing ProposalAccountMeta type").to.exist;
+    expect(ix.type.fields.map(function (f) { return norm(f.name); })).to.deep.equal([norm("program_id"), norm("accounts"), norm("data")]);
+    expect(meta.type.fields.map(function (f) { return norm(f.name); })).to.deep.equal([norm("pubkey"), norm("is_signer"), norm("is_writable")]);
+  });
+
+  it("checks remaining accounts against stored metas on execution", () => {
+    const ix = findInstruction(program.idl, "execute_proposal");
+    expect(accountNames(ix)).to.deep.equal([norm("dao"), norm("proposal")]);
+    const init = findInstruction(program.idl, "initialize_dao");
+    expect(accountNames(init)).to.include(norm("treasury"));
+    const errors = (program.idl.errors || []).map(function (e) { return norm(e.name); });
+    expect(errors).to.include.members([norm("MissingInstructionAccount"), norm("AccountMetaMismatch")]);
+  });
+
+  it("models the proposal lifecycle states", () => {
+    const types = Array.isArray(program.idl && program.idl.types) ? program.idl.types : [];
+    const state = types.find(function (t) { return norm(t.name) === norm("ProposalState"); });
+    expect(state, "Missing ProposalState type").to.exist;
+    expect(state.type.variants.map(function (v) { return norm(v.name); })).to.deep.equal(
+      ["Draft", "Active", "Succeeded", "Defeated", "Queued", "Executed", "Cancelled", "Expired"].map(norm)
+    );
+    const proposerOnly = ["activate_proposal", "cancel_proposal"];
+    proposerOnly.forEach(function (name) {
+      const proposer = findAccount(findInstruction(program.idl, name), "proposer");
+      expect(proposer, name + " must be signed by the proposer").to.exist;
+      expect(proposer.isSigner || proposer.signer || false).to.equal(true);
+    });
+    const errors = (program.idl.errors || []).map(function (e) { return norm(e.name); });
+    expect(errors).to.include.members(
+      ["InvalidProposalState", "VotingClosed", "VotingStillOpen", "TimelockActive", "ProposalExpired", "ExecutionWindowOpen"].map(norm)
+    );
+  });
+
+  it("only lets executed proposals amend the DAO config", () => {
+    // The DAO PDA signs only inside execute_proposal, so these calls must arrive as proposal CPIs.
+    ["set_quorum", "set_voting_period", "set_membership_rules"].forEach(function (name) {
+      const ix = findInstruction(program.idl, name);
+      expect(accountNames(ix), name + " must only take the dao").to.deep.equal([norm("dao")]);
+      const dao = findAccount(ix, "dao");
+      expect(dao.isSigner || dao.signer || false, name + " must require the DAO PDA signature").to.equal(true);
+      expect(dao.isMut || dao.writable || false).to.equal(true);
+      expect(accountNames(ix)).to.not.include(norm("authority"));
+    });
+    const errors = (program.idl.errors || []).map(function (e) { return norm(e.name); });
+    expect(errors).to.include.members(["NotGovernance", "InvalidMemberLimit", "MemberLimitReached"].map(norm));
+  });
+
+
+  it("exposes Dao/Proposal/Member/VoterDeposit/VoteRecord schemas", () => {
     const accs = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
     const asMap = Object.fromEntries(accs.map(function (acc) {
       const fields = Array.isArray(acc.type && acc.type.fields) ? acc.type.fields : [];
@@ -42,11 +186,34 @@ describe("governance-dao behavior", () => {
     }));
     expect(asMap[norm("Dao")]).to.exist;
     expect(asMap[norm("Proposal")]).to.exist;
+    expect(asMap[norm("Member")]).to.exist;
+    expect(asMap[norm("VoterDeposit")]).to.exist;
     expect(asMap[norm("VoteRecord")]).to.exist;
-    expect(asMap[norm("Dao")][norm("quorum")]).to.equal("u64");
+    expect(asMap[norm("Dao")][norm("quorum_bps")]).to.equal("u16");
     expect(asMap[norm("Dao")][norm("proposal_count")]).to.equal("u64");
-    expect(asMap[norm("Proposal")][norm("yes")]).to.equal("u64");
-    expect(asMap[norm("Proposal")][norm("executed")]).to.equal("bool");
+    expect(asMap[norm("Dao")][norm("member_count")]).to.equal("u32");
+    expect(asMap[norm("Dao")][norm("max_members")]).to.equal("u32");
+    expect(asMap[norm("Proposal")][norm("tally")]).to.equal("defined:VoteTally");
+    expect(asMap[norm("Proposal")][norm("state")]).to.equal("defined:ProposalState");
+    expect(asMap[norm("Proposal")][norm("voting_starts_at")]).to.equal("i64");
+    expect(asMap[norm("Proposal")][norm("voting_ends_at")]).to.equal("i64");
+    expect(asMap[norm("Proposal")][norm("executable_at")]).to.equal("i64");
+    expect(asMap[norm("Dao")][norm("pass_threshold_bps")]).to.equal("u16");
+    expect(asMap[norm("Dao")][norm("timelock_delay")]).to.equal("i64");
+    expect(asMap[norm("Proposal")][norm("action")]).to.equal("defined:ProposalAction");
+    expect(asMap[norm("Member")][norm("dao")]).to.be.oneOf(["publicKey", "pubkey"]);
+    expect(asMap[norm("Member")][norm("wallet")]).to.be.oneOf(["publicKey", "pubkey"]);
+    expect(asMap[norm("Proposal")][norm("quorum_votes")]).to.equal("u64");
+    expect(asMap[norm("Proposal")][norm("mode")]).to.equal("defined:VoteMode");
+    expect(asMap[norm("VoteRecord")][norm("choice")]).to.equal("defined:VoteChoice");
+    expect(asMap[norm("Proposal")][norm("instructions")]).to.equal("vec:defined:ProposalInstruction");
+    expect(asMap[norm("Dao")][norm("treasury_bump")]).to.equal("u8");
+    expect(asMap[norm("VoterDeposit")][norm("amount")]).to.equal("u64");
+    expect(asMap[norm("VoterDeposit")][norm("active_votes")]).to.equal("u32");
+    expect(asMap[norm("VoterDeposit")][norm("delegate")]).to.be.oneOf(["option:publicKey", "option:pubkey"]);
+    expect(asMap[norm("VoteRecord")][norm("cast_by")]).to.be.oneOf(["publicKey", "pubkey"]);
     expect(asMap[norm("VoteRecord")][norm("voted")]).to.equal("bool");
+    expect(asMap[norm("VoteRecord")][norm("ballot")]).to.equal("defined:Ballot");
+    expect(asMap[norm("VoterDeposit")][norm("credits_spent")]).to.equal("u64");
   });
 });
@@ -0,0 +1,560 @@
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SYSVAR_RENT_PUBKEY, SystemProgram, TransactionInstruction } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createTransferInstruction } from "@solana/spl-token";
+import { expect } from "chai";
+import {
+  createMint,
+  exists,
+  expectError,
+  lamports,
+  mintTo,
+  newWallet,
+  provider,
+  send,
+  startTemplate,
+  warp,
+} from "../../../template-tests/bankrun";
+
+const VOTING_PERIOD = 60;
+const TIMELOCK_DELAY = 10;
+const EXECUTION_WINDOW = 3600;
+const WHALE_DEPOSIT = 1_000;
+
+interface DaoFixture {
+  authority: Keypair;
+  dao: PublicKey;
+  mint: PublicKey;
+  vault: PublicKey;
+  treasury: PublicKey;
+}
+
+let program: Program;
+
+const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+const daoPda = (authority: PublicKey) => pda(Buffer.from("dao"), authority.toBuffer());
+const memberPda = (dao: PublicKey, wallet: PublicKey) => pda(Buffer.from("member"), dao.toBuffer(), wallet.toBuffer());
+const depositPda = (dao: PublicKey, voter: PublicKey) => pda(Buffer.from("deposit"), dao.toBuffer(), voter.toBuffer());
+const proposalPda = (dao: PublicKey, id: BN) => pda(Buffer.from("proposal"), dao.toBuffer(), id.toArrayLike(Buffer, "le", 8));
+const votePda = (proposal: PublicKey, voter: PublicKey) => pda(Buffer.from("vote"), proposal.toBuffer(), voter.toBuffer());
+
+// Mints `amount` governance tokens to the wallet and locks them in its deposit.
+async function deposit(f: DaoFixture, wallet: Keypair, amount: number): Promise<PublicKey> {
+  const tokens = await mintTo(f.mint, wallet.publicKey, amount);
+  await program.methods
+    .createDeposit()
+    .accountsStrict({
+      dao: f.dao,
+      deposit: depositPda(f.dao, wallet.publicKey),
+      voter: wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([wallet])
+    .rpc();
+  if (amount === 0) return tokens;
+  await program.methods
+    .depositTokens(new BN(amount))
+    .accountsStrict({
+      dao: f.dao,
+      deposit: depositPda(f.dao, wallet.publicKey),
+      vaultTokenAccount: f.vault,
+      voterTokenAccount: tokens,
+      voter: wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([wallet])
+    .rpc();
+  return tokens;
+}
+
+// A DAO whose authority holds enough deposited tokens to pass proposals alone.
+async function createDao(): Promise<DaoFixture> {
+  const authority = newWallet();
+  const mint = await createMint();
+  const dao = daoPda(authority.publicKey);
+  const f: DaoFixture = {
+    authority,
+    dao,
+    mint,
+    vault: pda(Buffer.from("vault"), dao.toBuffer()),
+    treasury: pda(Buffer.from("treasury"), dao.toBuffer()),
+  };
+  await program.methods
+    .initializeDao(2_000, 5_000, new BN(VOTING_PERIOD), new BN(TIMELOCK_DELAY), new BN(EXECUTION_WINDOW))
+    .accountsStrict({
+      dao,
+      member: memberPda(dao, authority.publicKey),
+      governanceMint: mint,
+      vaultTokenAccount: f.vault,
+      treasury: f.treasury,
+      authority: authority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
+    })
+    .signers([authority])
+    .rpc();
+  await deposit(f, authority, WHALE_DEPOSIT);
+  return f;
+}
+
+// Creates and activates a proposal; the authority is the proposer.
+async function createProposal(
+  f: DaoFixture,
+  opts: { action?: any; instructions?: any[]; mode?: any } = {}
+): Promise<PublicKey> {
+  const { proposalCount } = await program.account.dao.fetch(f.dao);
+  const proposal = proposalPda(f.dao, proposalCount);
+  await program.methods
+    .createProposal(proposalCount, opts.action ?? { none: {} }, opts.instructions ?? [], opts.mode ?? { binary: {} })
+    .accountsStrict({
+      dao: f.dao,
+      proposal,
+      governanceMint: f.mint,
+      creator: f.authority.publicKey,
+      authority: f.authority.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([f.authority])
+    .rpc();
+  await program.methods
+    .activateProposal()
+    .accountsStrict({ dao: f.dao, proposal, proposer: f.authority.publicKey })
+    .signers([f.authority])
+    .rpc();
+  return proposal;
+}
+
+function castVote(f: DaoFixture, proposal: PublicKey, voter: Keypair, choice: any) {
+  return program.methods
+    .castVote(choice)
+    .accountsStrict({
+      dao: f.dao,
+      proposal,
+      member: memberPda(f.dao, voter.publicKey),
+      deposit: depositPda(f.dao, voter.publicKey),
+      voteRecord: votePda(proposal, voter.publicKey),
+      voter: voter.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([voter])
+    .rpc();
+}
+
+function delegateVotes(f: DaoFixture, owner: Keypair, delegate: PublicKey) {
+  return program.methods
+    .delegateVotes(delegate)
+    .accountsStrict({ dao: f.dao, deposit: depositPda(f.dao, owner.publicKey), voter: owner.publicKey })
+    .signers([owner])
+    .rpc();
+}
+
+// `hops` are the intermediate voters between the delegator and the signing delegate.
+function castDelegatedVote(
+  f: DaoFixture,
+  proposal: PublicKey,
+  delegator: PublicKey,
+  delegate: Keypair,
+  choice: any,
+  hops: PublicKey[] = []
+) {
+  return program.methods
+    .castDelegatedVote(choice)
+    .accountsStrict({
+      dao: f.dao,
+      proposal,
+      member: memberPda(f.dao, delegator),
+      deposit: depositPda(f.dao, delegator),
+      voteRecord: votePda(proposal, delegator),
+      delegator,
+      delegate: delegate.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(hops.map((voter) => ({ pubkey: depositPda(f.dao, voter), isSigner: false, isWritable: false })))
+    .signers([delegate])
+    .rpc();
+}
+
+async function binaryTally(proposal: PublicKey) {
+  const { tally } = await program.account.proposal.fetch(proposal);
+  const { yes, no } = (tally as any).binary[0];
+  return { yes: yes.toNumber(), no: no.toNumber() };
+}
+
+// The authority votes yes, then the proposal is finalized, queued and past its timelock.
+async function passAndQueue(f: DaoFixture, proposal: PublicKey) {
+  await castVote(f, proposal, f.authority, { yes: {} });
+  await warp(VOTING_PERIOD);
+  await program.methods.finalizeProposal().accountsStrict({ dao: f.dao, proposal }).rpc();
+  await program.methods.queueProposal().accountsStrict({ dao: f.dao, proposal }).rpc();
+  await warp(TIMELOCK_DELAY);
+}
+
+async function addMember(f: DaoFixture, wallet: PublicKey) {
+  const proposal = await createProposal(f, { action: { addMember: { wallet } } });
+  await passAndQueue(f, proposal);
+  await program.methods
+    .executeAddMember()
+    .accountsStrict({
+      dao: f.dao,
+      proposal,
+      member: memberPda(f.dao, wallet),
+      wallet,
+      payer: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
+describe("governance-dao transactions", () => {
+  beforeEach(async () => {
+    program = await startTemplate("governance_dao");
+  });
+
+  it("rejects votes from wallets that are not members", async () => {
+    const f = await createDao();
+    const outsider = newWallet();
+    await deposit(f, outsider, 50);
+    const proposal = await createProposal(f);
+
+    // A deposit alone is not membership: the member PDA for the outsider was never created.
+    await expectError(castVote(f, proposal, outsider, { yes: {} }), "AccountNotInitialized");
+    const { yes } = await binaryTally(proposal);
+    expect(yes).to.equal(0);
+  });
+
+  it("rejects cross-DAO votes and executions", async () => {
+    const a = await createDao();
+    const b = await createDao();
+    const proposal = await createProposal(b);
+
+    // A member of DAO A, voting with A's member and deposit PDAs, on DAO B's proposal.
+    await expectError(castVote(a, proposal, a.authority, { yes: {} }), "DaoMismatch");
+
+    await passAndQueue(b, proposal);
+    await expectError(
+      program.methods.executeProposal().accountsStrict({ dao: a.dao, proposal }).rpc(),
+      "DaoMismatch"
+    );
+    await expectError(
+      program.methods.finalizeProposal().accountsStrict({ dao: a.dao, proposal }).rpc(),
+      "DaoMismatch"
+    );
+    // DAO B can still execute its own proposal.
+    await program.methods.executeProposal().accountsStrict({ dao: b.dao, proposal }).rpc();
+    const { state } = await program.account.proposal.fetch(proposal);
+    expect(state).to.deep.equal({ executed: {} });
+  });
+
+  it("returns a removed member's rent to their wallet", async () => {
+    const f = await createDao();
+    const removed = newWallet();
+    await addMember(f, removed.publicKey);
+    const member = memberPda(f.dao, removed.publicKey);
+    const rent = await lamports(member);
+    const proposal = await createProposal(f, { action: { removeMember: { wallet: removed.publicKey } } });
+    await passAndQueue(f, proposal);
+
+    const removeMember = (rentReceiver: PublicKey) =>
+      program.methods
+        .executeRemoveMember()
+        .accountsStrict({ dao: f.dao, proposal, member, rentReceiver })
+        .rpc();
+    // The provider wallet sends the transaction and tries to keep the rent.
+    await expectError(removeMember(provider.wallet.publicKey), "InvalidRentReceiver");
+
+    const before = await lamports(removed.publicKey);
+    await removeMember(removed.publicKey);
+    expect(await exists(member)).to.equal(false);
+    expect(await lamports(removed.publicKey)).to.equal(before + rent);
+  });
+
+  it("rejects a second vote with tokens moved to another wallet", async () => {
+    const f = await createDao();
+    const holder = newWallet();
+    const second = newWallet();
+    await addMember(f, holder.publicKey);
+    await addMember(f, second.publicKey);
+    const holderTokens = await deposit(f, holder, 50);
+    const secondTokens = await deposit(f, second, 0);
+    const proposal = await createProposal(f);
+
+    await castVote(f, proposal, holder, { yes: {} });
+    // The voted tokens stay in the DAO vault until the vote is relinquished.
+    await expectError(
+      program.methods
+        .withdrawTokens(new BN(50))
+        .accountsStrict({
+          dao: f.dao,
+          deposit: depositPda(f.dao, holder.publicKey),
+          vaultTokenAccount: f.vault,
+          voterTokenAccount: holderTokens,
+          voter: holder.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([holder])
+        .rpc(),
+      "TokensLocked"
+    );
+    // Nothing is left in the holder's wallet to hand over: SPL Token fails with InsufficientFunds.
+    await expectError(
+      send([createTransferInstruction(holderTokens, secondTokens, holder.publicKey, 50)], [holder]),
+      "custom program error: 0x1"
+    );
+    await expectError(castVote(f, proposal, second, { yes: {} }), "NoVotingPower");
+
+    // Unlocking the tokens mid-vote takes the holder's vote back out of the tally.
+    await program.methods
+      .relinquishVote()
+      .accountsStrict({
+        dao: f.dao,
+        proposal,
+        deposit: depositPda(f.dao, holder.publicKey),
+        voteRecord: votePda(proposal, holder.publicKey),
+        voter: holder.publicKey,
+      })
+      .signers([holder])
+      .rpc();
+    await program.methods
+      .withdrawTokens(new BN(50))
+      .accountsStrict({
+        dao: f.dao,
+        deposit: depositPda(f.dao, holder.publicKey),
+        vaultTokenAccount: f.vault,
+        voterTokenAccount: holderTokens,
+        voter: holder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([holder])
+      .rpc();
+    await send([createTransferInstruction(holderTokens, secondTokens, holder.publicKey, 50)], [holder]);
+    await program.methods
+      .depositTokens(new BN(50))
+      .accountsStrict({
+        dao: f.dao,
+        deposit: depositPda(f.dao, second.publicKey),
+        vaultTokenAccount: f.vault,
+        voterTokenAccount: secondTokens,
+        voter: second.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([second])
+      .rpc();
+    await castVote(f, proposal, second, { yes: {} });
+
+    const { yes } = await binaryTally(proposal);
+    expect(yes).to.equal(50);
+  });
+
+  it("rejects a stored instruction that re-enters execute_proposal", async () => {
+    const f = await createDao();
+    const { proposalCount } = await program.account.dao.fetch(f.dao);
+    const proposal = proposalPda(f.dao, proposalCount);
+    const reenter = await program.methods.executeProposal().accountsStrict({ dao: f.dao, proposal }).instruction();
+    await createProposal(f, {
+      instructions: [{ programId: reenter.programId, accounts: reenter.keys, data: reenter.data }],
+    });
+    await passAndQueue(f, proposal);
+
+    // The nested call reads the persisted Executed state and fails, reverting the outer call too.
+    await expectError(
+      program.methods
+        .executeProposal()
+        .accountsStrict({ dao: f.dao, proposal })
+        .remainingAccounts([...reenter.keys, { pubkey: program.programId, isSigner: false, isWritable: false }])
+        .rpc(),
+      "AlreadyExecuted"
+    );
+    const { state } = await program.account.proposal.fetch(proposal);
+    expect(state).to.deep.equal({ queued: {} });
+  });
+
+  it("counts a delegate's vote until the owner revokes and overrides it", async () => {
+    const f = await createDao();
+    const owner = newWallet();
+    const delegate = newWallet();
+    await addMember(f, owner.publicKey);
+    await deposit(f, owner, 40);
+    await delegateVotes(f, owner, delegate.publicKey);
+    const proposal = await createProposal(f);
+
+    await castDelegatedVote(f, proposal, owner.publicKey, delegate, { yes: {} });
+    let tally = await binaryTally(proposal);
+    expect(tally.yes).to.equal(40);
+    const record = await program.account.voteRecord.fetch(votePda(proposal, owner.publicKey));
+    expect(record.castBy.toBase58()).to.equal(delegate.publicKey.toBase58());
+    await expectError(castDelegatedVote(f, proposal, owner.publicKey, delegate, { no: {} }), "AlreadyVoted");
+
+    // Revoking mid-vote stops new delegated votes but leaves the cast one counted.
+    await program.methods
+      .revokeDelegation()
+      .accountsStrict({ dao: f.dao, deposit: depositPda(f.dao, owner.publicKey), voter: owner.publicKey })
+      .signers([owner])
+      .rpc();
+    await expectError(castDelegatedVote(f, proposal, owner.publicKey, delegate, { no: {} }), "NotDelegate");
+    tally = await binaryTally(proposal);
+    expect(tally.yes).to.equal(40);
+
+    // The owner's own cast_vote replaces the delegate's ballot without locking the deposit twice.
+    await castVote(f, proposal, owner, { no: {} });
+    tally = await binaryTally(proposal);
+    expect(tally.yes).to.equal(0);
+    expect(tally.no).to.equal(40);
+    const { activeVotes } = await program.account.voterDeposit.fetch(depositPda(f.dao, owner.publicKey));
+    expect(activeVotes).to.equal(1);
+    await expectError(castVote(f, proposal, owner, { yes: {} }), "AlreadyVoted");
+  });
+
+  it("follows delegation chains passed as remaining accounts", async () => {
+    const f = await createDao();
+    const [owner, middle, last, stranger] = [0, 1, 2, 3].map(() => newWallet());
+    await addMember(f, owner.publicKey);
+    await deposit(f, owner, 40);
+    await deposit(f, middle, 0);
+    await deposit(f, stranger, 0);
+    // owner -> middle -> last
+    await delegateVotes(f, owner, middle.publicKey);
+    await delegateVotes(f, middle, last.publicKey);
+    const proposal = await createProposal(f);
+
+    await expectError(castDelegatedVote(f, proposal, owner.publicKey, last, { yes: {} }), "NotDelegate");
+    await expectError(
+      castDelegatedVote(f, proposal, owner.publicKey, last, { yes: {} }, [stranger.publicKey]),
+      "NotDelegate"
+    );
+    await castDelegatedVote(f, proposal, owner.publicKey, last, { yes: {} }, [middle.publicKey]);
+    const { yes } = await binaryTally(proposal);
+    expect(yes).to.equal(40);
+    // The middle link is still a valid delegate, but the power has already been counted.
+    await expectError(castDelegatedVote(f, proposal, owner.publicKey, middle, { yes: {} }), "AlreadyVoted");
+  });
+
+  it("breaks a delegation chain when a middle link revokes", async () => {
+    const f = await createDao();
+    const [owner, middle, last] = [0, 1, 2].map(() => newWallet());
+    await addMember(f, owner.publicKey);
+    await deposit(f, owner, 40);
+    await deposit(f, middle, 0);
+    await expectError(delegateVotes(f, owner, owner.publicKey), "InvalidDelegate");
+    await delegateVotes(f, owner, middle.publicKey);
+    await delegateVotes(f, middle, last.publicKey);
+    const proposal = await createProposal(f);
+
+    await program.methods
+      .revokeDelegation()
+      .accountsStrict({ dao: f.dao, deposit: depositPda(f.dao, middle.publicKey), voter: middle.publicKey })
+      .signers([middle])
+      .rpc();
+    await expectError(
+      castDelegatedVote(f, proposal, owner.publicKey, last, { yes: {} }, [middle.publicKey]),
+      "NotDelegate"
+    );
+    // The owner's own delegation is untouched, so the middle link can still vote directly.
+    await castDelegatedVote(f, proposal, owner.publicKey, middle, { yes: {} });
+    const { yes } = await binaryTally(proposal);
+    expect(yes).to.equal(40);
+  });
+
+  it("caps the length of a delegation chain", async () => {
+    const f = await createDao();
+    const owner = newWallet();
+    const links = [0, 1, 2, 3, 4].map(() => newWallet());
+    await addMember(f, owner.publicKey);
+    await deposit(f, owner, 40);
+    await delegateVotes(f, owner, links[0].publicKey);
+    for (let i = 0; i < links.length - 1; i++) {
+      await deposit(f, links[i], 0);
+      await delegateVotes(f, links[i], links[i + 1].publicKey);
+    }
+    const proposal = await createProposal(f);
+
+    const hops = links.slice(0, -1).map((k) => k.publicKey);
+    await expectError(
+      castDelegatedVote(f, proposal, owner.publicKey, links[links.length - 1], { yes: {} }, hops),
+      "DelegationChainTooLong"
+    );
+  });
+
+  it("changes the DAO config only through an executed proposal", async () => {
+    const f = await createDao();
+    const setQuorum = await program.methods.setQuorum(3_000).accountsStrict({ dao: f.dao }).instruction();
+    const setVotingPeriod = await program.methods
+      .setVotingPeriod(new BN(120))
+      .accountsStrict({ dao: f.dao })
+      .instruction();
+
+    // Sent directly, nobody can provide the DAO PDA signature.
+    const unsigned = setQuorum.keys.map((key) => ({ ...key, isSigner: false }));
+    await expectError(
+      send([new TransactionInstruction({ programId: program.programId, keys: unsigned, data: setQuorum.data })]),
+      "NotGovernance"
+    );
+
+    const proposal = await createProposal(f, {
+      instructions: [setQuorum, setVotingPeriod].map((ix) => ({
+        programId: ix.programId,
+        accounts: ix.keys,
+        data: ix.data,
+      })),
+    });
+    await passAndQueue(f, proposal);
+    await program.methods
+      .executeProposal()
+      .accountsStrict({ dao: f.dao, proposal })
+      .remainingAccounts([...unsigned, { pubkey: program.programId, isSigner: false, isWritable: false }])
+      .rpc();
+
+    const dao = await program.account.dao.fetch(f.dao);
+    expect(dao.quorumBps).to.equal(3_000);
+    expect(dao.votingPeriod.toNumber()).to.equal(120);
+    const { state } = await program.account.proposal.fetch(proposal);
+    expect(state).to.deep.equal({ executed: {} });
+  });
+
+  it("debits quadratic credits and counts quorum in credits", async () => {
+    const f = await createDao();
+    const [alice, bob] = [newWallet(), newWallet()];
+    await addMember(f, alice.publicKey);
+    await addMember(f, bob.publicKey);
+    await deposit(f, alice, 100);
+    await deposit(f, bob, 100);
+    // Supply is 1_200, so a 20% quorum is 240 tokens' worth of credits.
+    const first = await createProposal(f, { mode: { quadratic: {} } });
+    const second = await createProposal(f, { mode: { quadratic: {} } });
+
+    await castVote(f, first, alice, { quadratic: { approve: true, credits: new BN(100) } });
+    await castVote(f, first, bob, { quadratic: { approve: true, credits: new BN(100) } });
+    // Alice's whole budget is locked in the first vote.
+    await expectError(
+      castVote(f, second, alice, { quadratic: { approve: true, credits: new BN(1) } }),
+      "InsufficientCredits"
+    );
+    const { creditsSpent } = await program.account.voterDeposit.fetch(depositPda(f.dao, alice.publicKey));
+    expect(creditsSpent.toNumber()).to.equal(100);
+
+    const proposal = await program.account.proposal.fetch(first);
+    const tally = (proposal.tally as any).quadratic[0];
+    expect(proposal.quorumVotes.toNumber()).to.equal(240);
+    expect(tally.yes.toNumber()).to.equal(20);
+    expect(tally.yesCredits.toNumber()).to.equal(200);
+
+    // 20 weight would clear a square-rooted quorum of 15, but 200 credits fall short of 240.
+    await warp(VOTING_PERIOD);
+    await program.methods.finalizeProposal().accountsStrict({ dao: f.dao, proposal: first }).rpc();
+    const { state } = await program.account.proposal.fetch(first);
+    expect(state).to.deep.equal({ defeated: {} });
+
+    await program.methods
+      .relinquishVote()
+      .accountsStrict({
+        dao: f.dao,
+        proposal: first,
+        deposit: depositPda(f.dao, alice.publicKey),
+        voteRecord: votePda(first, alice.publicKey),
+        voter: alice.publicKey,
+      })
+      .signers([alice])
+      .rpc();
+    const refunded = await program.account.voterDeposit.fetch(depositPda(f.dao, alice.publicKey));
+    expect(refunded.creditsSpent.toNumber()).to.equal(0);
+  });
+});
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 declare_id!("Auction111111111111111111111111111111");
 
@@ -22,6 +23,7 @@ pub mod marketplace_auction {
         auction.highest_bid = 0;
         auction.highest_bidder = Pubkey::default();
         auction.settled = false;
+        auction.quote_mint = None;
         auction.bump = ctx.bumps.vault;
 
         let vault = &mut ctx.accounts.vault;
@@ -32,9 +34,37 @@ pub mod marketplace_auction {
         Ok(())
     }
 
+    pub fn initialize_token_auction(
+        ctx: Context<InitializeTokenAuction>,
+        min_bid: u64,
+        end_ts: i64,
+    ) -> Result<()> {
+        require!(min_bid > 0, ErrorCode::InvalidBid);
+        let now = Clock::get()?.unix_timestamp;
+        require!(end_ts > now, ErrorCode::InvalidEndTime);
+
+        let auction = &mut ctx.accounts.auction;
+        auction.seller = ctx.accounts.seller.key();
+        auction.min_bid = min_bid;
+        auction.end_ts = end_ts;
+        auction.highest_bid = 0;
+        auction.highest_bidder = Pubkey::default();
+        auction.settled = false;
+        auction.quote_mint = Some(ctx.accounts.quote_mint.key());
+        auction.bump = ctx.bumps.vault;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.auction = auction.key();
+        vault.bump = ctx.bumps.vault;
+
+        msg!("Token auction initialized for mint {}", ctx.accounts.quote_mint.key());
+        Ok(())
+    }
+
     pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidBid);
         let auction = &mut ctx.accounts.auction;
+        require!(auction.quote_mint.is_none(), ErrorCode::WrongBidCurrency);
         let now = Clock::get()?.unix_timestamp;
         require!(now < auction.end_ts, ErrorCode::AuctionEnded);
         require!(amount >= auction.min_bid, ErrorCode::BidTooLow);
@@ -59,8 +89,57 @@ pub mod marketplace_auction {
         Ok(())
     }
 
+    pub fn place_token_bid(ctx: Context<PlaceTokenBid>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidBid);
+        let auction = &mut ctx.accounts.auction;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < auction.end_ts, ErrorCode::AuctionEnded);
+        require!(amount >= auction.min_bid, ErrorCode::BidTooLow);
+        require!(amount > auction.highest_bid, ErrorCode::BidTooLow);
+
+        let auction_key = auction.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", auction_key.as_ref(), &[auction.bump]]];
+
+        if auction.highest_bidder != Pubkey::default() {
+            require!(
+                ctx.accounts.previous_bidder_token_account.owner == auction.highest_bidder,
+                ErrorCode::InvalidPreviousBidder
+            );
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        to: ctx.accounts.previous_bidder_token_account.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                auction.highest_bid,
+            )?;
+        }
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.bidder_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.bidder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        auction.highest_bid = amount;
+        auction.highest_bidder = ctx.accounts.bidder.key();
+        msg!("Bid placed for {} tokens", amount);
+        Ok(())
+    }
+
     pub fn settle(ctx: Context<Settle>) -> Result<()> {
         let auction = &mut ctx.accounts.auction;
+        require!(auction.quote_mint.is_none(), ErrorCode::WrongBidCurrency);
         let now = Clock::get()?.unix_timestamp;
         require!(now >= auction.end_ts, ErrorCode::AuctionNotEnded);
         require!(!auction.settled, ErrorCode::AlreadySettled);
@@ -74,6 +153,34 @@ pub mod marketplace_auction {
         msg!("Auction settled");
         Ok(())
     }
+
+    pub fn settle_token(ctx: Context<SettleToken>) -> Result<()> {
+        let auction = &mut ctx.accounts.auction;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= auction.end_ts, ErrorCode::AuctionNotEnded);
+        require!(!auction.settled, ErrorCode::AlreadySettled);
+
+        if auction.highest_bid > 0 {
+            let auction_key = auction.key();
+            let signer_seeds: &[&[&[u8]]] = &[&[b"vault", auction_key.as_ref(), &[auction.bump]]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        to: ctx.accounts.seller_token_account.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                auction.highest_bid,
+            )?;
+        }
+
+        auction.settled = true;
+        msg!("Token auction settled");
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -93,6 +200,35 @@ pub struct InitializeAuction<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeTokenAuction<'info> {
+    #[account(init, payer = seller, space = 8 + Auction::LEN)]
+    pub auction: Account<'info, Auction>,
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Vault::LEN,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+    pub quote_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = seller,
+        token::mint = quote_mint,
+        token::authority = vault,
+        seeds = [b"vault_tokens", auction.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
 #[derive(Accounts)]
 pub struct PlaceBid<'info> {
     #[account(mut)]
@@ -106,6 +242,33 @@ pub struct PlaceBid<'info> {
     pub previous_bidder: UncheckedAccount<'info>,
 }
 
+#[derive(Accounts)]
+pub struct PlaceTokenBid<'info> {
+    #[account(mut, constraint = auction.quote_mint.is_some() @ ErrorCode::WrongBidCurrency)]
+    pub auction: Account<'info, Auction>,
+    #[account(seeds = [b"vault", auction.key().as_ref()], bump = auction.bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"vault_tokens", auction.key().as_ref()],
+        bump,
+        token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = Some(bidder_token_account.mint) == auction.quote_mint @ ErrorCode::InvalidQuoteMint
+    )]
+    pub bidder_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = Some(previous_bidder_token_account.mint) == auction.quote_mint @ ErrorCode::InvalidQuoteMint
+    )]
+    pub previous_bidder_token_account: Account<'info, TokenAccount>,
+    pub bidder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct Settle<'info> {
     #[account(mut)]
@@ -116,6 +279,33 @@ pub struct Settle<'info> {
     pub seller: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SettleToken<'info> {
+    #[account(
+        mut,
+        has_one = seller,
+        constraint = auction.quote_mint.is_some() @ ErrorCode::WrongBidCurrency
+    )]
+    pub auction: Account<'info, Auction>,
+    #[account(seeds = [b"vault", auction.key().as_ref()], bump = auction.bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"vault_tokens", auction.key().as_ref()],
+        bump,
+        token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = seller,
+        constraint = Some(seller_token_account.mint) == auction.quote_mint @ ErrorCode::InvalidQuoteMint
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[account]
 pub struct Auction {
     pub seller: Pubkey,
@@ -124,11 +314,12 @@ pub struct Auction {
     pub highest_bid: u64,
     pub highest_bidder: Pubkey,
     pub settled: bool,
+    pub quote_mint: Option<Pubkey>,
     pub bump: u8,
 }
 
 impl Auction {
-    pub const LEN: usize = 32 + 8 + 8 + 8 + 32 + 1 + 1;
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 32 + 1 + (1 + 32) + 1;
 }
 
 #[account]
@@ -157,4 +348,8 @@ pub enum ErrorCode {
     AuctionNotEnded,
     #[msg("Auction already settled")]
     AlreadySettled,
+    #[msg("Auction bids in a different currency")]
+    WrongBidCurrency,
+    #[msg("Token account mint does not match the auction quote mint")]
+    InvalidQuoteMint,
 }
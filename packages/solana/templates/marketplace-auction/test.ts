@@ -22,6 +22,35 @@ const INSTRUCTIONS = [
       { name: "previous_bidder", isMut: true, isSigner: false },
     ],
   },
+  {
+    name: "initialize_token_auction",
+    args: [
+      { name: "min_bid", type: "u64" },
+      { name: "end_ts", type: "i64" },
+    ],
+    accounts: [
+      { name: "auction", isMut: true, isSigner: false },
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "quote_mint", isMut: false, isSigner: false },
+      { name: "vault_token_account", isMut: true, isSigner: false },
+      { name: "seller", isMut: true, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+      { name: "system_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "place_token_bid",
+    args: [{ name: "amount", type: "u64" }],
+    accounts: [
+      { name: "auction", isMut: true, isSigner: false },
+      { name: "vault", isMut: false, isSigner: false },
+      { name: "vault_token_account", isMut: true, isSigner: false },
+      { name: "bidder_token_account", isMut: true, isSigner: false },
+      { name: "previous_bidder_token_account", isMut: true, isSigner: false },
+      { name: "bidder", isMut: false, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+    ],
+  },
   {
     name: "settle",
     args: [],
@@ -31,6 +60,18 @@ const INSTRUCTIONS = [
       { name: "seller", isMut: true, isSigner: true },
     ],
   },
+  {
+    name: "settle_token",
+    args: [],
+    accounts: [
+      { name: "auction", isMut: true, isSigner: false },
+      { name: "vault", isMut: false, isSigner: false },
+      { name: "vault_token_account", isMut: true, isSigner: false },
+      { name: "seller_token_account", isMut: true, isSigner: false },
+      { name: "seller", isMut: false, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+    ],
+  },
 ];
 
 function norm(name) {
@@ -46,6 +87,7 @@ function snakeToCamel(name) {
 function typeToString(type) {
   if (typeof type === "string") return type;
   if (type && typeof type === "object" && type.defined) return "defined:" + type.defined;
+  if (type && typeof type === "object" && type.option) return "option:" + typeToString(type.option);
   if (type && typeof type === "object" && type.array) {
     return "[" + typeToString(type.array[0]) + ";" + String(type.array[1]) + "]";
   }
@@ -122,6 +164,22 @@ describe("marketplace-auction behavior", () => {
     expect(settleBuilder && settleBuilder.rpc).to.be.a("function");
   });
 
+  it("exposes builder APIs for token-denominated auctions", () => {
+    const methods = program.methods || {};
+
+    const initializeBuilder = methods[snakeToCamel("initialize_token_auction")](1, 9999999999);
+    expect(initializeBuilder && initializeBuilder.accounts).to.be.a("function");
+    expect(initializeBuilder && initializeBuilder.rpc).to.be.a("function");
+
+    const placeBidBuilder = methods[snakeToCamel("place_token_bid")](2);
+    expect(placeBidBuilder && placeBidBuilder.accounts).to.be.a("function");
+    expect(placeBidBuilder && placeBidBuilder.rpc).to.be.a("function");
+
+    const settleBuilder = methods[snakeToCamel("settle_token")]();
+    expect(settleBuilder && settleBuilder.accounts).to.be.a("function");
+    expect(settleBuilder && settleBuilder.rpc).to.be.a("function");
+  });
+
   it("exposes Auction and Vault state schemas", () => {
     const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
     const asMap = Object.fromEntries(
@@ -145,6 +203,7 @@ describe("marketplace-auction behavior", () => {
     expect(asMap[norm("Auction")][norm("end_ts")]).to.equal("i64");
     expect(asMap[norm("Auction")][norm("highest_bid")]).to.equal("u64");
     expect(asMap[norm("Auction")][norm("settled")]).to.equal("bool");
+    expect(asMap[norm("Auction")][norm("quote_mint")]).to.match(/^option:/);
     expect(asMap[norm("Auction")][norm("bump")]).to.equal("u8");
     expect(asMap[norm("Vault")][norm("bump")]).to.equal("u8");
   });
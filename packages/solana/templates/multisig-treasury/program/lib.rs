@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_lang::system_program;
 
 declare_id!("MultiSig1111111111111111111111111111111");
 
@@ -6,15 +8,31 @@ declare_id!("MultiSig1111111111111111111111111111111");
 pub mod multisig_treasury {
     use super::*;
 
-    pub fn initialize_multisig(ctx: Context<InitializeMultisig>, threshold: u8) -> Result<()> {
-        require!(threshold > 0 && threshold <= 3, ErrorCode::InvalidThreshold);
+    pub fn initialize_multisig(
+        ctx: Context<InitializeMultisig>,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_owners(&owners)?;
+        require!(
+            threshold > 0 && threshold as usize <= owners.len(),
+            ErrorCode::InvalidThreshold
+        );
+        // Owners co-sign through remaining accounts, so nobody is enrolled without consent
+        // and a typo'd key cannot silently lower the number of live signers.
+        for owner in owners.iter() {
+            require!(
+                ctx.remaining_accounts
+                    .iter()
+                    .any(|account| account.key() == *owner && account.is_signer),
+                ErrorCode::MissingOwnerSignature
+            );
+        }
         let multisig = &mut ctx.accounts.multisig;
-        multisig.owners = [
-            ctx.accounts.owner_one.key(),
-            ctx.accounts.owner_two.key(),
-            ctx.accounts.owner_three.key(),
-        ];
+        multisig.owners = owners;
         multisig.threshold = threshold;
+        multisig.owner_set_seqno = 0;
+        multisig.transaction_count = 0;
         multisig.bump = ctx.bumps.vault;
 
         let vault = &mut ctx.accounts.vault;
@@ -25,61 +43,333 @@ pub mod multisig_treasury {
     }
 
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? += amount;
-        **ctx.accounts.depositor.to_account_info().try_borrow_mut_lamports()? -= amount;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        // The depositor is owned by the System Program, so lamports move through a CPI.
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
         msg!("Deposited {} lamports", amount);
         Ok(())
     }
 
-    pub fn create_proposal(ctx: Context<CreateProposal>, amount: u64) -> Result<()> {
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidAmount);
+        let multisig_key = ctx.accounts.multisig.key();
         let proposal = &mut ctx.accounts.proposal;
-        proposal.multisig = ctx.accounts.multisig.key();
+        proposal.init(
+            &mut ctx.accounts.multisig,
+            multisig_key,
+            ctx.accounts.proposer.key(),
+            expires_at,
+            ctx.bumps.proposal,
+        )?;
         proposal.recipient = ctx.accounts.recipient.key();
         proposal.amount = amount;
-        proposal.approvals = [false; 3];
-        proposal.executed = false;
+        proposal.config = None;
+        proposal.instructions = Vec::new();
         msg!("Proposal created");
         Ok(())
     }
 
+    pub fn create_transaction_proposal(
+        ctx: Context<CreateTransactionProposal>,
+        instructions: Vec<ProposalInstruction>,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(!instructions.is_empty(), ErrorCode::EmptyProposal);
+        let multisig_key = ctx.accounts.multisig.key();
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.init(
+            &mut ctx.accounts.multisig,
+            multisig_key,
+            ctx.accounts.proposer.key(),
+            expires_at,
+            ctx.bumps.proposal,
+        )?;
+        proposal.recipient = Pubkey::default();
+        proposal.amount = 0;
+        proposal.config = None;
+        proposal.instructions = instructions;
+        msg!("Transaction proposal created with {} instructions", proposal.instructions.len());
+        Ok(())
+    }
+
+    pub fn create_config_proposal(
+        ctx: Context<CreateConfigProposal>,
+        change: ConfigChange,
+        expires_at: i64,
+    ) -> Result<()> {
+        let multisig_key = ctx.accounts.multisig.key();
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.init(
+            &mut ctx.accounts.multisig,
+            multisig_key,
+            ctx.accounts.proposer.key(),
+            expires_at,
+            ctx.bumps.proposal,
+        )?;
+        proposal.recipient = Pubkey::default();
+        proposal.amount = 0;
+        proposal.config = Some(change);
+        proposal.instructions = Vec::new();
+        msg!("Config proposal created");
+        Ok(())
+    }
+
     pub fn approve(ctx: Context<Approve>) -> Result<()> {
         let idx = owner_index(&ctx.accounts.multisig, ctx.accounts.owner.key())?;
         let proposal = &mut ctx.accounts.proposal;
-        require!(!proposal.executed, ErrorCode::AlreadyExecuted);
+        proposal.assert_active()?;
+        require!(
+            proposal.owner_set_seqno == ctx.accounts.multisig.owner_set_seqno,
+            ErrorCode::StaleProposal
+        );
         proposal.approvals[idx] = true;
+        proposal.rejections[idx] = false;
         msg!("Owner approved proposal");
         Ok(())
     }
 
-    pub fn execute(ctx: Context<Execute>) -> Result<()> {
+    pub fn reject(ctx: Context<Reject>) -> Result<()> {
+        let idx = owner_index(&ctx.accounts.multisig, ctx.accounts.owner.key())?;
+        let threshold = ctx.accounts.multisig.threshold as usize;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.assert_active()?;
+        require!(
+            proposal.owner_set_seqno == ctx.accounts.multisig.owner_set_seqno,
+            ErrorCode::StaleProposal
+        );
+        proposal.rejections[idx] = true;
+        proposal.approvals[idx] = false;
+
+        // Once the remaining owners can no longer reach the threshold, the proposal is dead.
+        let rejections = proposal.rejections.iter().filter(|r| **r).count();
+        if proposal.rejections.len() - rejections < threshold {
+            proposal.status = ProposalStatus::Rejected;
+            msg!("Proposal rejected");
+        } else {
+            msg!("Owner rejected proposal");
+        }
+        Ok(())
+    }
+
+    pub fn cancel(ctx: Context<Cancel>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let proposal = &ctx.accounts.proposal;
+        proposal.assert_active()?;
+        require!(
+            authority == proposal.proposer || ctx.accounts.multisig.owners.contains(&authority),
+            ErrorCode::Unauthorized
+        );
+        // Anchor closes the account after the handler and refunds rent to the proposer.
+        msg!("Proposal cancelled");
+        Ok(())
+    }
+
+    pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            proposal.status != ProposalStatus::Active || now >= proposal.expires_at,
+            ErrorCode::ProposalStillActive
+        );
+        msg!("Proposal closed");
+        Ok(())
+    }
+
+    pub fn execute<'info>(ctx: Context<'_, '_, 'info, 'info, Execute<'info>>) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
-        require!(!proposal.executed, ErrorCode::AlreadyExecuted);
-        require!(proposal.amount > 0, ErrorCode::InvalidAmount);
-        require!(proposal.multisig == ctx.accounts.multisig.key(), ErrorCode::InvalidProposal);
+        proposal.assert_active()?;
+        proposal.assert_not_expired(Clock::get()?.unix_timestamp)?;
+        require!(
+            proposal.amount > 0 || !proposal.instructions.is_empty(),
+            ErrorCode::EmptyProposal
+        );
+        require!(proposal.config.is_none(), ErrorCode::InvalidProposal);
+        require!(
+            proposal.owner_set_seqno == ctx.accounts.multisig.owner_set_seqno,
+            ErrorCode::StaleProposal
+        );
 
         let approvals = proposal.approvals.iter().filter(|a| **a).count() as u8;
         require!(approvals >= ctx.accounts.multisig.threshold, ErrorCode::NotEnoughApprovals);
 
-        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= proposal.amount;
-        **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += proposal.amount;
-        proposal.executed = true;
-        msg!("Proposal executed");
+        // Write Executed into the account data now instead of at handler exit: a stored
+        // instruction that calls back into execute deserializes the proposal from its account.
+        proposal.status = ProposalStatus::Executed;
+        proposal.exit(&crate::ID)?;
+        if proposal.amount > 0 {
+            debit_vault(
+                &ctx.accounts.vault.to_account_info(),
+                &ctx.accounts.recipient.to_account_info(),
+                proposal.amount,
+            )?;
+        }
+
+        let multisig_key = ctx.accounts.multisig.key();
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", multisig_key.as_ref(), &[ctx.accounts.multisig.bump]]];
+        for stored in proposal.instructions.iter() {
+            let (ix, infos) = stored.resolve(ctx.remaining_accounts)?;
+            // The vault PDA signs, so it can own token accounts or act as an upgrade authority.
+            invoke_signed(&ix, &infos, signer_seeds)?;
+        }
+        msg!("Proposal executed with {} instructions", proposal.instructions.len());
         Ok(())
     }
-}
 
-fn owner_index(multisig: &Multisig, key: Pubkey) -> Result<usize> {
-    if key == multisig.owners[0] {
-        return Ok(0);
+    pub fn execute_config(ctx: Context<ExecuteConfig>) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.assert_active()?;
+        proposal.assert_not_expired(Clock::get()?.unix_timestamp)?;
+        require!(proposal.owner_set_seqno == multisig.owner_set_seqno, ErrorCode::StaleProposal);
+        let change = proposal.config.ok_or(ErrorCode::InvalidProposal)?;
+
+        let approvals = proposal.approvals.iter().filter(|a| **a).count() as u8;
+        require!(approvals >= multisig.threshold, ErrorCode::NotEnoughApprovals);
+
+        match change {
+            ConfigChange::AddOwner { owner } => {
+                require!(!multisig.owners.contains(&owner), ErrorCode::DuplicateOwner);
+                require!(multisig.owners.len() < MAX_OWNERS, ErrorCode::InvalidOwnerCount);
+                multisig.owners.push(owner);
+            }
+            ConfigChange::RemoveOwner { owner } => {
+                let idx = owner_index(multisig, owner)?;
+                require!(multisig.owners.len() > 1, ErrorCode::InvalidOwnerCount);
+                multisig.owners.remove(idx);
+                // Keep the threshold reachable by the remaining owners.
+                if multisig.threshold as usize > multisig.owners.len() {
+                    multisig.threshold = multisig.owners.len() as u8;
+                }
+            }
+            ConfigChange::ChangeThreshold { threshold } => {
+                require!(
+                    threshold > 0 && threshold as usize <= multisig.owners.len(),
+                    ErrorCode::InvalidThreshold
+                );
+                multisig.threshold = threshold;
+            }
+            // Spending limits need a new account, so they run through create_spending_limit.
+            ConfigChange::AddSpendingLimit { .. } => return err!(ErrorCode::InvalidProposal),
+        }
+        // Approvals are indexed by owner position, so pending proposals from the old set go stale.
+        if !matches!(change, ConfigChange::ChangeThreshold { .. }) {
+            multisig.owner_set_seqno = multisig
+                .owner_set_seqno
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        proposal.status = ProposalStatus::Executed;
+        msg!("Config proposal executed");
+        Ok(())
     }
-    if key == multisig.owners[1] {
-        return Ok(1);
+
+    pub fn create_spending_limit(ctx: Context<CreateSpendingLimit>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        let now = Clock::get()?.unix_timestamp;
+        proposal.assert_active()?;
+        proposal.assert_not_expired(now)?;
+        require!(proposal.owner_set_seqno == multisig.owner_set_seqno, ErrorCode::StaleProposal);
+        let (owner, amount, period) = match proposal.config {
+            Some(ConfigChange::AddSpendingLimit { owner, amount, period }) => (owner, amount, period),
+            _ => return err!(ErrorCode::InvalidProposal),
+        };
+
+        let approvals = proposal.approvals.iter().filter(|a| **a).count() as u8;
+        require!(approvals >= multisig.threshold, ErrorCode::NotEnoughApprovals);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(period > 0, ErrorCode::InvalidPeriod);
+        if let Some(owner) = owner {
+            owner_index(multisig, owner)?;
+        }
+
+        let limit = &mut ctx.accounts.spending_limit;
+        limit.multisig = multisig.key();
+        limit.owner = owner;
+        limit.amount = amount;
+        limit.period = period;
+        limit.remaining = amount;
+        limit.last_reset = now;
+        limit.bump = ctx.bumps.spending_limit;
+        proposal.status = ProposalStatus::Executed;
+        msg!("Spending limit created: {} lamports every {} seconds", amount, period);
+        Ok(())
     }
-    if key == multisig.owners[2] {
-        return Ok(2);
+
+    pub fn spend_from_limit(ctx: Context<SpendFromLimit>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let signer = ctx.accounts.owner.key();
+        owner_index(&ctx.accounts.multisig, signer)?;
+        let limit = &mut ctx.accounts.spending_limit;
+        // A limit without an owner can be spent by any owner.
+        if let Some(owner) = limit.owner {
+            require!(owner == signer, ErrorCode::Unauthorized);
+        }
+
+        limit.refresh(Clock::get()?.unix_timestamp)?;
+        require!(amount <= limit.remaining, ErrorCode::SpendingLimitExceeded);
+        limit.remaining -= amount;
+
+        debit_vault(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.recipient.to_account_info(),
+            amount,
+        )?;
+        msg!("Spent {} lamports, {} left this period", amount, limit.remaining);
+        Ok(())
     }
-    Err(ErrorCode::Unauthorized.into())
+}
+
+pub const MAX_OWNERS: usize = 10;
+
+fn validate_owners(owners: &[Pubkey]) -> Result<()> {
+    require!(
+        !owners.is_empty() && owners.len() <= MAX_OWNERS,
+        ErrorCode::InvalidOwnerCount
+    );
+    for (i, owner) in owners.iter().enumerate() {
+        require!(!owners[..i].contains(owner), ErrorCode::DuplicateOwner);
+    }
+    Ok(())
+}
+
+// Moves lamports out of the vault without dropping it below the rent-exempt minimum.
+fn debit_vault(vault: &AccountInfo, recipient: &AccountInfo, amount: u64) -> Result<()> {
+    let floor = Rent::get()?.minimum_balance(vault.data_len());
+    let remaining = vault
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::InsufficientFunds)?;
+    require!(remaining >= floor, ErrorCode::BelowRentExempt);
+    let credited = recipient
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    **vault.try_borrow_mut_lamports()? = remaining;
+    **recipient.try_borrow_mut_lamports()? = credited;
+    Ok(())
+}
+
+fn owner_index(multisig: &Multisig, key: Pubkey) -> Result<usize> {
+    multisig
+        .owners
+        .iter()
+        .position(|owner| *owner == key)
+        .ok_or_else(|| ErrorCode::Unauthorized.into())
 }
 
 #[derive(Accounts)]
@@ -96,16 +386,13 @@ pub struct InitializeMultisig<'info> {
     pub vault: Account<'info, Vault>,
     #[account(mut)]
     pub payer: Signer<'info>,
-    pub owner_one: Signer<'info>,
-    pub owner_two: Signer<'info>,
-    pub owner_three: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     pub multisig: Account<'info, Multisig>,
-    #[account(seeds = [b"vault", multisig.key().as_ref()], bump = multisig.bump)]
+    #[account(mut, seeds = [b"vault", multisig.key().as_ref()], bump = multisig.bump)]
     pub vault: Account<'info, Vault>,
     #[account(mut)]
     pub depositor: Signer<'info>,
@@ -114,10 +401,17 @@ pub struct Deposit<'info> {
 
 #[derive(Accounts)]
 pub struct CreateProposal<'info> {
+    #[account(mut)]
     pub multisig: Account<'info, Multisig>,
     #[account(seeds = [b"vault", multisig.key().as_ref()], bump = multisig.bump)]
     pub vault: Account<'info, Vault>,
-    #[account(init, payer = proposer, space = 8 + Proposal::LEN)]
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::space(&[]),
+        seeds = [b"proposal", multisig.key().as_ref(), &multisig.transaction_count.to_le_bytes()],
+        bump
+    )]
     pub proposal: Account<'info, Proposal>,
     /// CHECK: Recipient can be any account.
     pub recipient: UncheckedAccount<'info>,
@@ -127,34 +421,154 @@ pub struct CreateProposal<'info> {
 }
 
 #[derive(Accounts)]
-pub struct Approve<'info> {
+pub struct CreateConfigProposal<'info> {
+    #[account(mut)]
     pub multisig: Account<'info, Multisig>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::space(&[]),
+        seeds = [b"proposal", multisig.key().as_ref(), &multisig.transaction_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
     #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(instructions: Vec<ProposalInstruction>)]
+pub struct CreateTransactionProposal<'info> {
+    #[account(mut)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::space(&instructions),
+        seeds = [b"proposal", multisig.key().as_ref(), &multisig.transaction_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Approve<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, has_one = multisig @ ErrorCode::InvalidProposal)]
     pub proposal: Account<'info, Proposal>,
     pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct Execute<'info> {
+pub struct Reject<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, has_one = multisig @ ErrorCode::InvalidProposal)]
+    pub proposal: Account<'info, Proposal>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Cancel<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        mut,
+        has_one = multisig @ ErrorCode::InvalidProposal,
+        has_one = proposer @ ErrorCode::InvalidProposer,
+        close = proposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: Only receives the rent refund; must match proposal.proposer.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
     pub multisig: Account<'info, Multisig>,
+    #[account(
+        mut,
+        has_one = multisig @ ErrorCode::InvalidProposal,
+        has_one = proposer @ ErrorCode::InvalidProposer,
+        close = proposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: Only receives the rent refund; must match proposal.proposer.
     #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, seeds = [b"vault", multisig.key().as_ref()], bump = multisig.bump)]
     pub vault: Account<'info, Vault>,
+    #[account(mut, has_one = multisig @ ErrorCode::InvalidProposal)]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: Must be the proposal recipient whenever lamports move.
+    #[account(
+        mut,
+        constraint = proposal.amount == 0 || recipient.key() == proposal.recipient
+            @ ErrorCode::InvalidRecipient
+    )]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfig<'info> {
     #[account(mut)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, has_one = multisig @ ErrorCode::InvalidProposal)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSpendingLimit<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, has_one = multisig @ ErrorCode::InvalidProposal)]
     pub proposal: Account<'info, Proposal>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SpendingLimit::LEN,
+        seeds = [b"spending_limit", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+        bump
+    )]
+    pub spending_limit: Account<'info, SpendingLimit>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SpendFromLimit<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, seeds = [b"vault", multisig.key().as_ref()], bump = multisig.bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, has_one = multisig @ ErrorCode::InvalidSpendingLimit)]
+    pub spending_limit: Account<'info, SpendingLimit>,
     /// CHECK: Recipient can be any account.
     #[account(mut)]
     pub recipient: UncheckedAccount<'info>,
+    pub owner: Signer<'info>,
 }
 
 #[account]
 pub struct Multisig {
-    pub owners: [Pubkey; 3],
+    pub owners: Vec<Pubkey>,
     pub threshold: u8,
+    pub owner_set_seqno: u32,
+    pub transaction_count: u64,
     pub bump: u8,
 }
 
 impl Multisig {
-    pub const LEN: usize = (32 * 3) + 1 + 1;
+    // Space is reserved for MAX_OWNERS so owners can be added without a realloc.
+    pub const LEN: usize = (4 + 32 * MAX_OWNERS) + 1 + 4 + 8 + 1;
 }
 
 #[account]
@@ -170,19 +584,197 @@ impl Vault {
 #[account]
 pub struct Proposal {
     pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
     pub recipient: Pubkey,
     pub amount: u64,
-    pub approvals: [bool; 3],
-    pub executed: bool,
+    pub config: Option<ConfigChange>,
+    pub approvals: Vec<bool>,
+    pub rejections: Vec<bool>,
+    pub owner_set_seqno: u32,
+    pub status: ProposalStatus,
+    pub expires_at: i64,
+    pub bump: u8,
+    pub instructions: Vec<ProposalInstruction>,
 }
 
 impl Proposal {
-    pub const LEN: usize = 32 + 32 + 8 + 3 + 1;
+    pub const LEN: usize = 32
+        + 8
+        + 32
+        + 32
+        + 8
+        + (1 + ConfigChange::LEN)
+        + (4 + MAX_OWNERS)
+        + (4 + MAX_OWNERS)
+        + 4
+        + 1
+        + 8
+        + 1
+        + 4;
+
+    // Shared setup for every proposal kind: checks the proposer, takes the next transaction index.
+    pub fn init(
+        &mut self,
+        multisig: &mut Multisig,
+        multisig_key: Pubkey,
+        proposer: Pubkey,
+        expires_at: i64,
+        bump: u8,
+    ) -> Result<()> {
+        // Only owners may propose; otherwise anyone could burn transaction indexes.
+        owner_index(multisig, proposer)?;
+        require!(expires_at > Clock::get()?.unix_timestamp, ErrorCode::InvalidExpiry);
+        self.multisig = multisig_key;
+        self.index = multisig.transaction_count;
+        self.proposer = proposer;
+        self.approvals = vec![false; multisig.owners.len()];
+        self.rejections = vec![false; multisig.owners.len()];
+        self.owner_set_seqno = multisig.owner_set_seqno;
+        self.status = ProposalStatus::Active;
+        self.expires_at = expires_at;
+        self.bump = bump;
+        multisig.transaction_count = multisig
+            .transaction_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn assert_active(&self) -> Result<()> {
+        match self.status {
+            ProposalStatus::Active => Ok(()),
+            ProposalStatus::Executed => err!(ErrorCode::AlreadyExecuted),
+            ProposalStatus::Rejected => err!(ErrorCode::ProposalNotActive),
+        }
+    }
+
+    pub fn assert_not_expired(&self, now: i64) -> Result<()> {
+        require!(now < self.expires_at, ErrorCode::ProposalExpired);
+        Ok(())
+    }
+
+    pub fn space(instructions: &[ProposalInstruction]) -> usize {
+        Self::LEN
+            + instructions
+                .iter()
+                .map(ProposalInstruction::serialized_len)
+                .sum::<usize>()
+    }
+}
+
+#[account]
+pub struct SpendingLimit {
+    pub multisig: Pubkey,
+    pub owner: Option<Pubkey>,
+    pub amount: u64,
+    pub period: i64,
+    pub remaining: u64,
+    pub last_reset: i64,
+    pub bump: u8,
+}
+
+impl SpendingLimit {
+    pub const LEN: usize = 32 + (1 + 32) + 8 + 8 + 8 + 8 + 1;
+
+    // Advance last_reset by whole periods so the window stays aligned to creation time.
+    pub fn refresh(&mut self, now: i64) -> Result<()> {
+        let elapsed = now.checked_sub(self.last_reset).ok_or(ErrorCode::MathOverflow)?;
+        if elapsed >= self.period {
+            let periods = elapsed / self.period;
+            self.last_reset = self
+                .last_reset
+                .checked_add(periods * self.period)
+                .ok_or(ErrorCode::MathOverflow)?;
+            self.remaining = self.amount;
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+impl ProposalInstruction {
+    pub fn serialized_len(&self) -> usize {
+        32 + 4 + self.accounts.len() * ProposalAccountMeta::LEN + 4 + self.data.len()
+    }
+
+    // Looks up each stored key in the accounts passed to execute; the program account goes
+    // last, after the instruction's own accounts, as invoke_signed expects.
+    pub fn resolve<'info>(
+        &self,
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<(Instruction, Vec<AccountInfo<'info>>)> {
+        let lookup = |key: &Pubkey| {
+            remaining
+                .iter()
+                .find(|account| account.key == key)
+                .ok_or(ErrorCode::MissingInstructionAccount)
+        };
+        let mut metas = Vec::with_capacity(self.accounts.len());
+        let mut infos = Vec::with_capacity(self.accounts.len() + 1);
+        for meta in self.accounts.iter() {
+            let info = lookup(&meta.pubkey)?;
+            require!(!meta.is_writable || info.is_writable, ErrorCode::AccountMetaMismatch);
+            metas.push(AccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            });
+            infos.push(info.clone());
+        }
+        let program = lookup(&self.program_id)?;
+        require!(program.executable, ErrorCode::AccountMetaMismatch);
+        infos.push(program.clone());
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data: self.data.clone(),
+        };
+        Ok((ix, infos))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ProposalAccountMeta {
+    pub const LEN: usize = 32 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    Active,
+    Executed,
+    Rejected,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigChange {
+    AddOwner { owner: Pubkey },
+    RemoveOwner { owner: Pubkey },
+    ChangeThreshold { threshold: u8 },
+    AddSpendingLimit { owner: Option<Pubkey>, amount: u64, period: i64 },
+}
+
+impl ConfigChange {
+    // Sized for the largest variant, AddSpendingLimit.
+    pub const LEN: usize = 1 + (1 + 32) + 8 + 8;
 }
 
 #[error_code]
 pub enum ErrorCode {
-    #[msg("Threshold must be between 1 and 3")]
+    #[msg("Threshold must be between 1 and the number of owners")]
     InvalidThreshold,
     #[msg("Amount must be greater than zero")]
     InvalidAmount,
@@ -194,4 +786,42 @@ pub enum ErrorCode {
     InvalidProposal,
     #[msg("Not enough approvals")]
     NotEnoughApprovals,
+    #[msg("Multisig must have between 1 and 10 owners")]
+    InvalidOwnerCount,
+    #[msg("Owner is already part of the multisig")]
+    DuplicateOwner,
+    #[msg("Owner set changed since the proposal was created")]
+    StaleProposal,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Proposal has nothing to execute")]
+    EmptyProposal,
+    #[msg("Instruction account missing from remaining accounts")]
+    MissingInstructionAccount,
+    #[msg("Supplied account does not match the stored meta")]
+    AccountMetaMismatch,
+    #[msg("Recipient does not match the proposal")]
+    InvalidRecipient,
+    #[msg("Proposal is no longer active")]
+    ProposalNotActive,
+    #[msg("Proposal has expired")]
+    ProposalExpired,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Proposer does not match the proposal")]
+    InvalidProposer,
+    #[msg("Active proposals can only be closed after they expire")]
+    ProposalStillActive,
+    #[msg("Spending limit period must be positive")]
+    InvalidPeriod,
+    #[msg("Amount exceeds the remaining spending limit")]
+    SpendingLimitExceeded,
+    #[msg("Spending limit does not match multisig")]
+    InvalidSpendingLimit,
+    #[msg("Every owner must sign multisig creation")]
+    MissingOwnerSignature,
+    #[msg("Vault does not hold enough lamports")]
+    InsufficientFunds,
+    #[msg("Transfer would leave the vault below its rent-exempt minimum")]
+    BelowRentExempt,
 }
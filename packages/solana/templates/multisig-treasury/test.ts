@@ -1,14 +1,14 @@
 const INSTRUCTIONS = [
   {
     name: "initialize_multisig",
-    args: [{ name: "threshold", type: "u8" }],
+    args: [
+      { name: "owners", type: "vec:publicKey" },
+      { name: "threshold", type: "u8" },
+    ],
     accounts: [
       { name: "multisig", isMut: true, isSigner: false },
       { name: "vault", isMut: true, isSigner: false },
       { name: "payer", isMut: true, isSigner: true },
-      { name: "owner_one", isMut: false, isSigner: true },
-      { name: "owner_two", isMut: false, isSigner: true },
-      { name: "owner_three", isMut: false, isSigner: true },
       { name: "system_program", isMut: false, isSigner: false },
     ],
   },
@@ -24,9 +24,12 @@ const INSTRUCTIONS = [
   },
   {
     name: "create_proposal",
-    args: [{ name: "amount", type: "u64" }],
+    args: [
+      { name: "amount", type: "u64" },
+      { name: "expires_at", type: "i64" },
+    ],
     accounts: [
-      { name: "multisig", isMut: false, isSigner: false },
+      { name: "multisig", isMut: true, isSigner: false },
       { name: "vault", isMut: false, isSigner: false },
       { name: "proposal", isMut: true, isSigner: false },
       { name: "recipient", isMut: false, isSigner: false },
@@ -34,6 +37,32 @@ const INSTRUCTIONS = [
       { name: "system_program", isMut: false, isSigner: false },
     ],
   },
+  {
+    name: "create_transaction_proposal",
+    args: [
+      { name: "instructions", type: "vec:defined:ProposalInstruction" },
+      { name: "expires_at", type: "i64" },
+    ],
+    accounts: [
+      { name: "multisig", isMut: true, isSigner: false },
+      { name: "proposal", isMut: true, isSigner: false },
+      { name: "proposer", isMut: true, isSigner: true },
+      { name: "system_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "create_config_proposal",
+    args: [
+      { name: "change", type: "defined:ConfigChange" },
+      { name: "expires_at", type: "i64" },
+    ],
+    accounts: [
+      { name: "multisig", isMut: true, isSigner: false },
+      { name: "proposal", isMut: true, isSigner: false },
+      { name: "proposer", isMut: true, isSigner: true },
+      { name: "system_program", isMut: false, isSigner: false },
+    ],
+  },
   {
     name: "approve",
     args: [],
@@ -43,6 +72,34 @@ const INSTRUCTIONS = [
       { name: "owner", isMut: false, isSigner: true },
     ],
   },
+  {
+    name: "reject",
+    args: [],
+    accounts: [
+      { name: "multisig", isMut: false, isSigner: false },
+      { name: "proposal", isMut: true, isSigner: false },
+      { name: "owner", isMut: false, isSigner: true },
+    ],
+  },
+  {
+    name: "cancel",
+    args: [],
+    accounts: [
+      { name: "multisig", isMut: false, isSigner: false },
+      { name: "proposal", isMut: true, isSigner: false },
+      { name: "proposer", isMut: true, isSigner: false },
+      { name: "authority", isMut: false, isSigner: true },
+    ],
+  },
+  {
+    name: "close_proposal",
+    args: [],
+    accounts: [
+      { name: "multisig", isMut: false, isSigner: false },
+      { name: "proposal", isMut: true, isSigner: false },
+      { name: "proposer", isMut: true, isSigner: false },
+    ],
+  },
   {
     name: "execute",
     args: [],
@@ -53,6 +110,36 @@ const INSTRUCTIONS = [
       { name: "recipient", isMut: true, isSigner: false },
     ],
   },
+  {
+    name: "execute_config",
+    args: [],
+    accounts: [
+      { name: "multisig", isMut: true, isSigner: false },
+      { name: "proposal", isMut: true, isSigner: false },
+    ],
+  },
+  {
+    name: "create_spending_limit",
+    args: [],
+    accounts: [
+      { name: "multisig", isMut: false, isSigner: false },
+      { name: "proposal", isMut: true, isSigner: false },
+      { name: "spending_limit", isMut: true, isSigner: false },
+      { name: "payer", isMut: true, isSigner: true },
+      { name: "system_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "spend_from_limit",
+    args: [{ name: "amount", type: "u64" }],
+    accounts: [
+      { name: "multisig", isMut: false, isSigner: false },
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "spending_limit", isMut: true, isSigner: false },
+      { name: "recipient", isMut: true, isSigner: false },
+      { name: "owner", isMut: false, isSigner: true },
+    ],
+  },
 ];
 
 function norm(name) {
@@ -68,6 +155,8 @@ function snakeToCamel(name) {
 function typeToString(type) {
   if (typeof type === "string") return type;
   if (type && typeof type === "object" && type.defined) return "defined:" + type.defined;
+  if (type && typeof type === "object" && type.vec) return "vec:" + typeToString(type.vec);
+  if (type && typeof type === "object" && type.option) return "option:" + typeToString(type.option);
   if (type && typeof type === "object" && type.array) {
     return "[" + typeToString(type.array[0]) + ";" + String(type.array[1]) + "]";
   }
@@ -116,7 +205,7 @@ describe("multisig-treasury behavior", () => {
         const actualArg = actualArgs[index];
         expect(actualArg, "Missing arg #" + index + " in " + expectedInstruction.name).to.exist;
         expect(norm(actualArg.name), expectedInstruction.name + " arg name mismatch").to.equal(norm(expectedArg.name));
-        expect(typeToString(actualArg.type), expectedInstruction.name + " arg type mismatch").to.equal(expectedArg.type);
+        expect(typeToString(actualArg.type).replace("pubkey", "publicKey"), expectedInstruction.name + " arg type mismatch").to.equal(expectedArg.type);
       });
 
       expectedInstruction.accounts.forEach(function (expectedAccount) {
@@ -131,15 +220,17 @@ describe("multisig-treasury behavior", () => {
   it("exposes builder APIs for full proposal lifecycle", () => {
     const methods = program.methods || {};
 
-    const initializeBuilder = methods[snakeToCamel("initialize_multisig")](2);
+    const initializeBuilder = methods[snakeToCamel("initialize_multisig")]([provider.wallet.publicKey], 1);
     expect(initializeBuilder && initializeBuilder.accounts).to.be.a("function");
     expect(initializeBuilder && initializeBuilder.rpc).to.be.a("function");
 
+    const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+
     const depositBuilder = methods[snakeToCamel("deposit")](1);
     expect(depositBuilder && depositBuilder.accounts).to.be.a("function");
     expect(depositBuilder && depositBuilder.rpc).to.be.a("function");
 
-    const createBuilder = methods[snakeToCamel("create_proposal")](1);
+    const createBuilder = methods[snakeToCamel("create_proposal")](1, expiresAt);
     expect(createBuilder && createBuilder.accounts).to.be.a("function");
     expect(createBuilder && createBuilder.rpc).to.be.a("function");
 
@@ -150,6 +241,151 @@ describe("multisig-treasury behavior", () => {
     const executeBuilder = methods[snakeToCamel("execute")]();
     expect(executeBuilder && executeBuilder.accounts).to.be.a("function");
     expect(executeBuilder && executeBuilder.rpc).to.be.a("function");
+
+    const transactionBuilder = methods[snakeToCamel("create_transaction_proposal")]([], expiresAt);
+    expect(transactionBuilder && transactionBuilder.rpc).to.be.a("function");
+
+    const configBuilder = methods[snakeToCamel("create_config_proposal")]({ changeThreshold: { threshold: 1 } }, expiresAt);
+    expect(configBuilder && configBuilder.rpc).to.be.a("function");
+
+    const rejectBuilder = methods[snakeToCamel("reject")]();
+    expect(rejectBuilder && rejectBuilder.rpc).to.be.a("function");
+
+    const cancelBuilder = methods[snakeToCamel("cancel")]();
+    expect(cancelBuilder && cancelBuilder.rpc).to.be.a("function");
+
+    const closeBuilder = methods[snakeToCamel("close_proposal")]();
+    expect(closeBuilder && closeBuilder.rpc).to.be.a("function");
+
+    const limitBuilder = methods[snakeToCamel("create_spending_limit")]();
+    expect(limitBuilder && limitBuilder.rpc).to.be.a("function");
+
+    const spendBuilder = methods[snakeToCamel("spend_from_limit")](1);
+    expect(spendBuilder && spendBuilder.rpc).to.be.a("function");
+
+    const executeConfigBuilder = methods[snakeToCamel("execute_config")]();
+    expect(executeConfigBuilder && executeConfigBuilder.rpc).to.be.a("function");
+  });
+
+  it("stores arbitrary instructions executed by the vault PDA", () => {
+    const types = Array.isArray(program.idl && program.idl.types) ? program.idl.types : [];
+    const fieldsOf = function (name) {
+      const type = types.find(function (t) {
+        return norm(t.name) === norm(name);
+      });
+      expect(type, name + " type missing").to.exist;
+      return type.type.fields.map(function (f) {
+        return norm(f.name);
+      });
+    };
+    expect(fieldsOf("ProposalInstruction")).to.deep.equal(["program_id", "accounts", "data"].map(norm));
+    expect(fieldsOf("ProposalAccountMeta")).to.deep.equal(["pubkey", "is_signer", "is_writable"].map(norm));
+
+    // The vault signs with [b"vault", multisig], so execute must bind it to that PDA.
+    const vault = findAccount(findInstruction(program.idl, "execute"), "vault");
+    if (vault.pda) {
+      const seeds = vault.pda.seeds.map(function (s) {
+        return s.kind === "const" ? Buffer.from(s.value).toString() : norm(s.path);
+      });
+      expect(seeds).to.deep.equal(["vault", norm("multisig")]);
+    }
+
+    const errors = (program.idl.errors || []).map(function (e) {
+      return norm(e.name);
+    });
+    expect(errors).to.include.members(["EmptyProposal", "MissingInstructionAccount", "AccountMetaMismatch"].map(norm));
+  });
+
+  it("changes owners and threshold only through approved proposals", () => {
+    const types = Array.isArray(program.idl && program.idl.types) ? program.idl.types : [];
+    const change = types.find(function (t) {
+      return norm(t.name) === norm("ConfigChange");
+    });
+    expect(change, "ConfigChange type missing").to.exist;
+    expect(
+      change.type.variants.map(function (v) {
+        return norm(v.name);
+      })
+    ).to.deep.equal(["AddOwner", "RemoveOwner", "ChangeThreshold", "AddSpendingLimit"].map(norm));
+
+    // No instruction lets a single signer rewrite the owner set directly.
+    const executeConfig = findInstruction(program.idl, "execute_config");
+    (executeConfig.accounts || []).forEach(function (account) {
+      expect(isSigner(account), "execute_config must not rely on a signer").to.equal(false);
+    });
+
+    const errors = (program.idl.errors || []).map(function (e) {
+      return norm(e.name);
+    });
+    expect(errors).to.include.members(["InvalidThreshold", "InvalidOwnerCount", "DuplicateOwner", "StaleProposal"].map(norm));
+  });
+
+  it("tracks rejection, cancellation and expiry with rent returned to the proposer", () => {
+    const types = Array.isArray(program.idl && program.idl.types) ? program.idl.types : [];
+    const status = types.find(function (t) {
+      return norm(t.name) === norm("ProposalStatus");
+    });
+    expect(status, "ProposalStatus type missing").to.exist;
+    expect(
+      status.type.variants.map(function (v) {
+        return norm(v.name);
+      })
+    ).to.deep.equal(["Active", "Executed", "Rejected"].map(norm));
+
+    // Cancel and close send rent to the recorded proposer, never to an arbitrary signer.
+    ["cancel", "close_proposal"].forEach(function (name) {
+      const instruction = findInstruction(program.idl, name);
+      const proposer = findAccount(instruction, "proposer");
+      expect(isSigner(proposer), name + ".proposer must not need to sign").to.equal(false);
+      const proposal = findAccount(instruction, "proposal");
+      if (proposal.relations) {
+        expect(proposal.relations.map(norm)).to.include.members(["multisig", "proposer"].map(norm));
+      }
+    });
+
+    const errors = (program.idl.errors || []).map(function (e) {
+      return norm(e.name);
+    });
+    expect(errors).to.include.members(
+      ["ProposalNotActive", "ProposalExpired", "InvalidExpiry", "InvalidProposer", "ProposalStillActive"].map(norm)
+    );
+  });
+
+  it("lets owners spend alone within a per-period spending limit", () => {
+    const types = Array.isArray(program.idl && program.idl.types) ? program.idl.types : [];
+    const change = types.find(function (t) {
+      return norm(t.name) === norm("ConfigChange");
+    });
+    const addLimit = change.type.variants.find(function (v) {
+      return norm(v.name) === norm("AddSpendingLimit");
+    });
+    expect(
+      addLimit.fields.map(function (f) {
+        return norm(f.name) + ":" + typeToString(f.type).replace("pubkey", "publicKey");
+      })
+    ).to.deep.equal(["owner:option:publicKey", "amount:u64", "period:i64"]);
+
+    // Limits are only minted from an approved proposal, never by a lone signer.
+    const create = findInstruction(program.idl, "create_spending_limit");
+    expect(findAccount(create, "proposal"), "create_spending_limit needs a proposal").to.exist;
+    const limit = findAccount(create, "spending_limit");
+    if (limit.pda) {
+      expect(Buffer.from(limit.pda.seeds[0].value).toString()).to.equal("spending_limit");
+    }
+
+    const spend = findInstruction(program.idl, "spend_from_limit");
+    expect(
+      (spend.accounts || []).filter(isSigner).map(function (a) {
+        return norm(a.name);
+      })
+    ).to.deep.equal([norm("owner")]);
+
+    const errors = (program.idl.errors || []).map(function (e) {
+      return norm(e.name);
+    });
+    expect(errors).to.include.members(["InvalidPeriod", "SpendingLimitExceeded", "InvalidSpendingLimit"].map(norm));
+    // Payouts must leave the vault rent-exempt, whether from a proposal or a spending limit.
+    expect(errors).to.include.members(["InsufficientFunds", "BelowRentExempt"].map(norm));
   });
 
   it("exposes Multisig, Vault, and Proposal state schemas", () => {
@@ -171,11 +407,28 @@ describe("multisig-treasury behavior", () => {
     expect(asMap[norm("Multisig")], "Multisig schema missing").to.exist;
     expect(asMap[norm("Vault")], "Vault schema missing").to.exist;
     expect(asMap[norm("Proposal")], "Proposal schema missing").to.exist;
+    expect(asMap[norm("SpendingLimit")], "SpendingLimit schema missing").to.exist;
 
+    expect(asMap[norm("Multisig")][norm("owners")]).to.be.oneOf(["vec:publicKey", "vec:pubkey"]);
     expect(asMap[norm("Multisig")][norm("threshold")]).to.equal("u8");
+    expect(asMap[norm("Multisig")][norm("owner_set_seqno")]).to.equal("u32");
+    expect(asMap[norm("Multisig")][norm("transaction_count")]).to.equal("u64");
     expect(asMap[norm("Multisig")][norm("bump")]).to.equal("u8");
     expect(asMap[norm("Vault")][norm("bump")]).to.equal("u8");
+    expect(asMap[norm("Proposal")][norm("index")]).to.equal("u64");
+    expect(asMap[norm("Proposal")][norm("bump")]).to.equal("u8");
     expect(asMap[norm("Proposal")][norm("amount")]).to.equal("u64");
-    expect(asMap[norm("Proposal")][norm("executed")]).to.equal("bool");
+    expect(asMap[norm("Proposal")][norm("proposer")]).to.be.oneOf(["publicKey", "pubkey"]);
+    expect(asMap[norm("Proposal")][norm("status")]).to.equal("defined:ProposalStatus");
+    expect(asMap[norm("Proposal")][norm("expires_at")]).to.equal("i64");
+    expect(asMap[norm("Proposal")][norm("rejections")]).to.equal("vec:bool");
+    expect(asMap[norm("SpendingLimit")][norm("owner")]).to.be.oneOf(["option:publicKey", "option:pubkey"]);
+    expect(asMap[norm("SpendingLimit")][norm("amount")]).to.equal("u64");
+    expect(asMap[norm("SpendingLimit")][norm("period")]).to.equal("i64");
+    expect(asMap[norm("SpendingLimit")][norm("remaining")]).to.equal("u64");
+    expect(asMap[norm("SpendingLimit")][norm("last_reset")]).to.equal("i64");
+    expect(asMap[norm("Proposal")][norm("approvals")]).to.equal("vec:bool");
+    expect(asMap[norm("Proposal")][norm("config")]).to.equal("option:defined:ConfigChange");
+    expect(asMap[norm("Proposal")][norm("instructions")]).to.equal("vec:defined:ProposalInstruction");
   });
 });
@@ -0,0 +1,441 @@
+// multisig_treasury before its account validation was hardened, deployed next to the fixed
+// program so the suite can run each substitution attack against both. As in the original
+// report, Execute.vault has no seeds check, Approve does not tie the proposal to the multisig,
+// Deposit.vault is read-only and proposals live at caller-chosen keypair addresses.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+
+declare_id!("MultiSig1111111111111111111111111111111");
+
+#[program]
+pub mod multisig_treasury {
+    use super::*;
+
+    pub fn initialize_multisig(
+        ctx: Context<InitializeMultisig>,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_owners(&owners)?;
+        require!(
+            threshold > 0 && threshold as usize <= owners.len(),
+            ErrorCode::InvalidThreshold
+        );
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.owner_set_seqno = 0;
+        multisig.bump = ctx.bumps.vault;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.multisig = multisig.key();
+        vault.bump = ctx.bumps.vault;
+        msg!("Multisig initialized");
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? += amount;
+        **ctx.accounts.depositor.to_account_info().try_borrow_mut_lamports()? -= amount;
+        msg!("Deposited {} lamports", amount);
+        Ok(())
+    }
+
+    pub fn create_proposal(ctx: Context<CreateProposal>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = ctx.accounts.multisig.key();
+        proposal.recipient = ctx.accounts.recipient.key();
+        proposal.amount = amount;
+        proposal.config = None;
+        proposal.approvals = vec![false; ctx.accounts.multisig.owners.len()];
+        proposal.owner_set_seqno = ctx.accounts.multisig.owner_set_seqno;
+        proposal.executed = false;
+        proposal.instructions = Vec::new();
+        msg!("Proposal created");
+        Ok(())
+    }
+
+    pub fn create_transaction_proposal(
+        ctx: Context<CreateTransactionProposal>,
+        instructions: Vec<ProposalInstruction>,
+    ) -> Result<()> {
+        require!(!instructions.is_empty(), ErrorCode::EmptyProposal);
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = multisig.key();
+        proposal.recipient = Pubkey::default();
+        proposal.amount = 0;
+        proposal.config = None;
+        proposal.approvals = vec![false; multisig.owners.len()];
+        proposal.owner_set_seqno = multisig.owner_set_seqno;
+        proposal.executed = false;
+        proposal.instructions = instructions;
+        msg!("Transaction proposal created with {} instructions", proposal.instructions.len());
+        Ok(())
+    }
+
+    pub fn create_config_proposal(
+        ctx: Context<CreateConfigProposal>,
+        change: ConfigChange,
+    ) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = multisig.key();
+        proposal.recipient = Pubkey::default();
+        proposal.amount = 0;
+        proposal.config = Some(change);
+        proposal.approvals = vec![false; multisig.owners.len()];
+        proposal.owner_set_seqno = multisig.owner_set_seqno;
+        proposal.executed = false;
+        proposal.instructions = Vec::new();
+        msg!("Config proposal created");
+        Ok(())
+    }
+
+    pub fn approve(ctx: Context<Approve>) -> Result<()> {
+        let idx = owner_index(&ctx.accounts.multisig, ctx.accounts.owner.key())?;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, ErrorCode::AlreadyExecuted);
+        require!(
+            proposal.owner_set_seqno == ctx.accounts.multisig.owner_set_seqno,
+            ErrorCode::StaleProposal
+        );
+        proposal.approvals[idx] = true;
+        msg!("Owner approved proposal");
+        Ok(())
+    }
+
+    pub fn execute<'info>(ctx: Context<'_, '_, 'info, 'info, Execute<'info>>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, ErrorCode::AlreadyExecuted);
+        require!(
+            proposal.amount > 0 || !proposal.instructions.is_empty(),
+            ErrorCode::EmptyProposal
+        );
+        require!(proposal.multisig == ctx.accounts.multisig.key(), ErrorCode::InvalidProposal);
+        require!(proposal.config.is_none(), ErrorCode::InvalidProposal);
+        require!(
+            proposal.owner_set_seqno == ctx.accounts.multisig.owner_set_seqno,
+            ErrorCode::StaleProposal
+        );
+
+        let approvals = proposal.approvals.iter().filter(|a| **a).count() as u8;
+        require!(approvals >= ctx.accounts.multisig.threshold, ErrorCode::NotEnoughApprovals);
+
+        // Mark executed before any CPI so a re-entrant call cannot run the proposal twice.
+        proposal.executed = true;
+        if proposal.amount > 0 {
+            **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= proposal.amount;
+            **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += proposal.amount;
+        }
+
+        let multisig_key = ctx.accounts.multisig.key();
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", multisig_key.as_ref(), &[ctx.accounts.vault.bump]]];
+        for stored in proposal.instructions.iter() {
+            let mut metas = Vec::with_capacity(stored.accounts.len());
+            let mut infos = Vec::with_capacity(stored.accounts.len() + 1);
+            for meta in stored.accounts.iter() {
+                let info = ctx
+                    .remaining_accounts
+                    .iter()
+                    .find(|account| account.key() == meta.pubkey)
+                    .ok_or(ErrorCode::MissingInstructionAccount)?;
+                require!(!meta.is_writable || info.is_writable, ErrorCode::AccountMetaMismatch);
+                metas.push(if meta.is_writable {
+                    AccountMeta::new(meta.pubkey, meta.is_signer)
+                } else {
+                    AccountMeta::new_readonly(meta.pubkey, meta.is_signer)
+                });
+                infos.push(info.clone());
+            }
+            let program = ctx
+                .remaining_accounts
+                .iter()
+                .find(|account| account.key() == stored.program_id)
+                .ok_or(ErrorCode::MissingInstructionAccount)?;
+            require!(program.executable, ErrorCode::AccountMetaMismatch);
+            infos.push(program.clone());
+
+            let ix = Instruction {
+                program_id: stored.program_id,
+                accounts: metas,
+                data: stored.data.clone(),
+            };
+            // The vault PDA signs, so it can own token accounts or act as an upgrade authority.
+            invoke_signed(&ix, &infos, signer_seeds)?;
+        }
+        msg!("Proposal executed with {} instructions", proposal.instructions.len());
+        Ok(())
+    }
+
+    pub fn execute_config(ctx: Context<ExecuteConfig>) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, ErrorCode::AlreadyExecuted);
+        require!(proposal.multisig == multisig.key(), ErrorCode::InvalidProposal);
+        require!(proposal.owner_set_seqno == multisig.owner_set_seqno, ErrorCode::StaleProposal);
+        let change = proposal.config.ok_or(ErrorCode::InvalidProposal)?;
+
+        let approvals = proposal.approvals.iter().filter(|a| **a).count() as u8;
+        require!(approvals >= multisig.threshold, ErrorCode::NotEnoughApprovals);
+
+        match change {
+            ConfigChange::AddOwner { owner } => {
+                require!(!multisig.owners.contains(&owner), ErrorCode::DuplicateOwner);
+                require!(multisig.owners.len() < MAX_OWNERS, ErrorCode::InvalidOwnerCount);
+                multisig.owners.push(owner);
+            }
+            ConfigChange::RemoveOwner { owner } => {
+                let idx = owner_index(multisig, owner)?;
+                require!(multisig.owners.len() > 1, ErrorCode::InvalidOwnerCount);
+                multisig.owners.remove(idx);
+                // Keep the threshold reachable by the remaining owners.
+                if multisig.threshold as usize > multisig.owners.len() {
+                    multisig.threshold = multisig.owners.len() as u8;
+                }
+            }
+            ConfigChange::ChangeThreshold { threshold } => {
+                require!(
+                    threshold > 0 && threshold as usize <= multisig.owners.len(),
+                    ErrorCode::InvalidThreshold
+                );
+                multisig.threshold = threshold;
+            }
+        }
+        // Approvals are indexed by owner position, so pending proposals from the old set go stale.
+        if !matches!(change, ConfigChange::ChangeThreshold { .. }) {
+            multisig.owner_set_seqno = multisig
+                .owner_set_seqno
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        proposal.executed = true;
+        msg!("Config proposal executed");
+        Ok(())
+    }
+}
+
+pub const MAX_OWNERS: usize = 10;
+
+fn validate_owners(owners: &[Pubkey]) -> Result<()> {
+    require!(
+        !owners.is_empty() && owners.len() <= MAX_OWNERS,
+        ErrorCode::InvalidOwnerCount
+    );
+    for (i, owner) in owners.iter().enumerate() {
+        require!(!owners[..i].contains(owner), ErrorCode::DuplicateOwner);
+    }
+    Ok(())
+}
+
+fn owner_index(multisig: &Multisig, key: Pubkey) -> Result<usize> {
+    multisig
+        .owners
+        .iter()
+        .position(|owner| *owner == key)
+        .ok_or_else(|| ErrorCode::Unauthorized.into())
+}
+
+#[derive(Accounts)]
+pub struct InitializeMultisig<'info> {
+    #[account(init, payer = payer, space = 8 + Multisig::LEN)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Vault::LEN,
+        seeds = [b"vault", multisig.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(seeds = [b"vault", multisig.key().as_ref()], bump = multisig.bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(seeds = [b"vault", multisig.key().as_ref()], bump = multisig.bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(init, payer = proposer, space = 8 + Proposal::space(&[]))]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: Recipient can be any account.
+    pub recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateConfigProposal<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(init, payer = proposer, space = 8 + Proposal::space(&[]))]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(instructions: Vec<ProposalInstruction>)]
+pub struct CreateTransactionProposal<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(init, payer = proposer, space = 8 + Proposal::space(&instructions))]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Approve<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: Recipient can be any account.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfig<'info> {
+    #[account(mut)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[account]
+pub struct Multisig {
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub owner_set_seqno: u32,
+    pub bump: u8,
+}
+
+impl Multisig {
+    // Space is reserved for MAX_OWNERS so owners can be added without a realloc.
+    pub const LEN: usize = (4 + 32 * MAX_OWNERS) + 1 + 4 + 1;
+}
+
+#[account]
+pub struct Vault {
+    pub multisig: Pubkey,
+    pub bump: u8,
+}
+
+impl Vault {
+    pub const LEN: usize = 32 + 1;
+}
+
+#[account]
+pub struct Proposal {
+    pub multisig: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub config: Option<ConfigChange>,
+    pub approvals: Vec<bool>,
+    pub owner_set_seqno: u32,
+    pub executed: bool,
+    pub instructions: Vec<ProposalInstruction>,
+}
+
+impl Proposal {
+    pub const LEN: usize = 32 + 32 + 8 + (1 + ConfigChange::LEN) + (4 + MAX_OWNERS) + 4 + 1 + 4;
+
+    pub fn space(instructions: &[ProposalInstruction]) -> usize {
+        Self::LEN
+            + instructions
+                .iter()
+                .map(ProposalInstruction::serialized_len)
+                .sum::<usize>()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+impl ProposalInstruction {
+    pub fn serialized_len(&self) -> usize {
+        32 + 4 + self.accounts.len() * ProposalAccountMeta::LEN + 4 + self.data.len()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ProposalAccountMeta {
+    pub const LEN: usize = 32 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigChange {
+    AddOwner { owner: Pubkey },
+    RemoveOwner { owner: Pubkey },
+    ChangeThreshold { threshold: u8 },
+}
+
+impl ConfigChange {
+    pub const LEN: usize = 1 + 32;
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Threshold must be between 1 and the number of owners")]
+    InvalidThreshold,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Owner is not part of the multisig")]
+    Unauthorized,
+    #[msg("Proposal already executed")]
+    AlreadyExecuted,
+    #[msg("Proposal does not match multisig")]
+    InvalidProposal,
+    #[msg("Not enough approvals")]
+    NotEnoughApprovals,
+    #[msg("Multisig must have between 1 and 10 owners")]
+    InvalidOwnerCount,
+    #[msg("Owner is already part of the multisig")]
+    DuplicateOwner,
+    #[msg("Owner set changed since the proposal was created")]
+    StaleProposal,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Proposal has nothing to execute")]
+    EmptyProposal,
+    #[msg("Instruction account missing from remaining accounts")]
+    MissingInstructionAccount,
+    #[msg("Supplied account does not match the stored meta")]
+    AccountMetaMismatch,
+}
@@ -0,0 +1,524 @@
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, Transaction, TransactionInstruction } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  exists,
+  expectError,
+  lamports,
+  loadProgram,
+  newWallet,
+  now,
+  provider,
+  send,
+  startTemplate,
+} from "../../../template-tests/bankrun";
+
+interface MultisigFixture {
+  owners: Keypair[];
+  multisig: PublicKey;
+  vault: PublicKey;
+}
+
+let program: Program;
+
+const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+const vaultPda = (multisig: PublicKey) => pda(Buffer.from("vault"), multisig.toBuffer());
+const proposalPda = (multisig: PublicKey, index: BN) => pda(Buffer.from("proposal"), multisig.toBuffer(), index.toArrayLike(Buffer, "le", 8));
+
+const signerMetas = (keys: PublicKey[]) => keys.map((pubkey) => ({ pubkey, isSigner: true, isWritable: false }));
+
+// Each signer is passed as a remaining account; pass a subset of the owners to leave some out.
+function initializeMultisig(
+  multisig: Keypair,
+  owners: PublicKey[],
+  threshold: number,
+  signers: Keypair[]
+) {
+  return program.methods
+    .initializeMultisig(owners, threshold)
+    .accountsStrict({
+      multisig: multisig.publicKey,
+      vault: vaultPda(multisig.publicKey),
+      payer: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(signerMetas(signers.map((s) => s.publicKey)))
+    .signers([multisig, ...signers])
+    .rpc();
+}
+
+async function expiresIn(seconds: number): Promise<BN> {
+  return new BN(((await now()) + BigInt(seconds)).toString());
+}
+
+async function createMultisig(ownerCount = 3, threshold = 2): Promise<MultisigFixture> {
+  const owners = Array.from({ length: ownerCount }, () => newWallet());
+  const multisig = Keypair.generate();
+  await initializeMultisig(multisig, owners.map((o) => o.publicKey), threshold, owners);
+  return { owners, multisig: multisig.publicKey, vault: vaultPda(multisig.publicKey) };
+}
+
+// The address the next proposal of this multisig will be created at.
+async function nextProposal(multisig: PublicKey): Promise<PublicKey> {
+  const { transactionCount } = await program.account.multisig.fetch(multisig);
+  return proposalPda(multisig, transactionCount);
+}
+
+async function createTransactionProposal(f: MultisigFixture, instructions: TransactionInstruction[]): Promise<PublicKey> {
+  const proposal = await nextProposal(f.multisig);
+  const stored = instructions.map((ix) => ({ programId: ix.programId, accounts: ix.keys, data: ix.data }));
+  await program.methods
+    .createTransactionProposal(stored, await expiresIn(3600))
+    .accountsStrict({
+      multisig: f.multisig,
+      proposal,
+      proposer: f.owners[0].publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([f.owners[0]])
+    .rpc();
+  return proposal;
+}
+
+function deposit(f: MultisigFixture, depositor: Keypair, amount: number) {
+  return program.methods
+    .deposit(new BN(amount))
+    .accountsStrict({
+      multisig: f.multisig,
+      vault: f.vault,
+      depositor: depositor.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([depositor])
+    .rpc();
+}
+
+// A lamport transfer proposal from the vault, created by the first owner.
+async function createProposal(f: MultisigFixture, recipient: PublicKey, amount: number): Promise<PublicKey> {
+  const proposal = await nextProposal(f.multisig);
+  await program.methods
+    .createProposal(new BN(amount), await expiresIn(3600))
+    .accountsStrict({
+      multisig: f.multisig,
+      vault: f.vault,
+      proposal,
+      recipient,
+      proposer: f.owners[0].publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([f.owners[0]])
+    .rpc();
+  return proposal;
+}
+
+function execute(accounts: { multisig: PublicKey; vault: PublicKey; proposal: PublicKey; recipient: PublicKey }) {
+  return program.methods.execute().accountsStrict(accounts).rpc();
+}
+
+function approve(multisig: PublicKey, proposal: PublicKey, owner: Keypair) {
+  return program.methods
+    .approve()
+    .accountsStrict({ multisig, proposal, owner: owner.publicKey })
+    .signers([owner])
+    .rpc();
+}
+
+// Approves with the first `threshold` owners.
+async function approveToThreshold(f: MultisigFixture, proposal: PublicKey) {
+  const { threshold } = await program.account.multisig.fetch(f.multisig);
+  for (const owner of f.owners.slice(0, threshold)) {
+    await approve(f.multisig, proposal, owner);
+  }
+}
+
+// Passes an AddSpendingLimit proposal and creates the limit it describes.
+async function createSpendingLimit(f: MultisigFixture, owner: PublicKey | null, amount: number): Promise<PublicKey> {
+  const { transactionCount } = await program.account.multisig.fetch(f.multisig);
+  const proposal = proposalPda(f.multisig, transactionCount);
+  const change = { addSpendingLimit: { owner, amount: new BN(amount), period: new BN(86_400) } };
+  await program.methods
+    .createConfigProposal(change, await expiresIn(3600))
+    .accountsStrict({
+      multisig: f.multisig,
+      proposal,
+      proposer: f.owners[0].publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([f.owners[0]])
+    .rpc();
+  await approveToThreshold(f, proposal);
+  const spendingLimit = pda(Buffer.from("spending_limit"), f.multisig.toBuffer(), transactionCount.toArrayLike(Buffer, "le", 8));
+  await program.methods
+    .createSpendingLimit()
+    .accountsStrict({
+      multisig: f.multisig,
+      proposal,
+      spendingLimit,
+      payer: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+  return spendingLimit;
+}
+
+describe("multisig-treasury transactions", () => {
+  beforeEach(async () => {
+    program = await startTemplate("multisig_treasury");
+  });
+
+  it("requires every owner to sign multisig creation", async () => {
+    const [alice, bob] = [Keypair.generate(), Keypair.generate()];
+    const owners = [alice.publicKey, bob.publicKey];
+    const multisig = Keypair.generate();
+
+    await expectError(initializeMultisig(multisig, owners, 1, [alice]), "MissingOwnerSignature");
+
+    // Listing bob as a non-signer is rejected the same way as leaving bob out.
+    await expectError(
+      program.methods
+        .initializeMultisig(owners, 1)
+        .accountsStrict({
+          multisig: multisig.publicKey,
+          vault: vaultPda(multisig.publicKey),
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([...signerMetas([alice.publicKey]), { pubkey: bob.publicKey, isSigner: false, isWritable: false }])
+        .signers([multisig, alice])
+        .rpc(),
+      "MissingOwnerSignature"
+    );
+
+    await initializeMultisig(multisig, owners, 1, [alice, bob]);
+    const state = await program.account.multisig.fetch(multisig.publicKey);
+    expect(state.owners.map((o) => o.toBase58())).to.deep.equal(owners.map((o) => o.toBase58()));
+  });
+
+  it("rejects a stored instruction that re-enters execute", async () => {
+    const f = await createMultisig();
+    const proposal = await nextProposal(f.multisig);
+    const accounts = { multisig: f.multisig, vault: f.vault, proposal, recipient: f.vault };
+    const reenter = await program.methods.execute().accountsStrict(accounts).instruction();
+    await createTransactionProposal(f, [reenter]);
+    await approveToThreshold(f, proposal);
+
+    // The vault-signed call back into execute finds Executed already written and fails,
+    // which reverts the outer execute as well.
+    await expectError(
+      program.methods
+        .execute()
+        .accountsStrict(accounts)
+        .remainingAccounts([...reenter.keys, { pubkey: program.programId, isSigner: false, isWritable: false }])
+        .rpc(),
+      "AlreadyExecuted"
+    );
+    const { status } = await program.account.proposal.fetch(proposal);
+    expect(status).to.deep.equal({ active: {} });
+  });
+
+  it("rejects another multisig's vault on execute", async () => {
+    const f = await createMultisig();
+    const other = await createMultisig();
+    await deposit(other, other.owners[0], 1_000_000);
+    const recipient = Keypair.generate().publicKey;
+    const proposal = await createProposal(f, recipient, 1_000_000);
+    await approveToThreshold(f, proposal);
+
+    await expectError(
+      execute({ multisig: f.multisig, vault: other.vault, proposal, recipient }),
+      "ConstraintSeeds"
+    );
+    expect(await exists(recipient)).to.equal(false);
+  });
+
+  it("rejects approving or executing a proposal through another multisig", async () => {
+    const f = await createMultisig();
+    const other = await createMultisig(1, 1);
+    await deposit(f, f.owners[0], 1_000_000);
+    const recipient = Keypair.generate().publicKey;
+    const proposal = await createProposal(f, recipient, 1_000_000);
+
+    // other's sole owner would meet other's threshold on its own.
+    await expectError(approve(other.multisig, proposal, other.owners[0]), "InvalidProposal");
+    await expectError(
+      execute({ multisig: other.multisig, vault: other.vault, proposal, recipient }),
+      "InvalidProposal"
+    );
+    const { approvals } = await program.account.proposal.fetch(proposal);
+    expect(approvals).to.deep.equal([false, false, false]);
+  });
+
+  it("rejects redirecting an approved transfer to another recipient", async () => {
+    const f = await createMultisig();
+    await deposit(f, f.owners[0], 1_000_000);
+    const proposal = await createProposal(f, Keypair.generate().publicKey, 1_000_000);
+    await approveToThreshold(f, proposal);
+
+    await expectError(
+      execute({ multisig: f.multisig, vault: f.vault, proposal, recipient: f.owners[2].publicKey }),
+      "InvalidRecipient"
+    );
+  });
+
+  it("rejects zero-lamport deposits and a read-only vault", async () => {
+    const f = await createMultisig();
+    const depositor = f.owners[0];
+    await expectError(deposit(f, depositor, 0), "InvalidAmount");
+
+    const ix = await program.methods
+      .deposit(new BN(1_000_000))
+      .accountsStrict({
+        multisig: f.multisig,
+        vault: f.vault,
+        depositor: depositor.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+    ix.keys.find((meta) => meta.pubkey.equals(f.vault))!.isWritable = false;
+    await expectError(provider.sendAndConfirm(new Transaction().add(ix), [depositor]), "ConstraintMut");
+  });
+
+  it("creates proposals only at the PDA of the next transaction index", async () => {
+    const f = await createMultisig();
+    const proposer = f.owners[0];
+    const keypair = Keypair.generate();
+    const create = async (proposal: PublicKey, signers: Keypair[]) =>
+      program.methods
+        .createProposal(new BN(1), await expiresIn(3600))
+        .accountsStrict({
+          multisig: f.multisig,
+          vault: f.vault,
+          proposal,
+          recipient: proposer.publicKey,
+          proposer: proposer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers(signers)
+        .rpc();
+
+    await expectError(create(keypair.publicKey, [proposer, keypair]), "ConstraintSeeds");
+    // An index the counter has not reached yet is rejected the same way.
+    await expectError(create(proposalPda(f.multisig, new BN(1)), [proposer]), "ConstraintSeeds");
+
+    await create(proposalPda(f.multisig, new BN(0)), [proposer]);
+    const { index } = await program.account.proposal.fetch(proposalPda(f.multisig, new BN(0)));
+    expect(index.toNumber()).to.equal(0);
+    const { transactionCount } = await program.account.multisig.fetch(f.multisig);
+    expect(transactionCount.toNumber()).to.equal(1);
+  });
+
+  it("rejects proposals from non-owners without using a transaction index", async () => {
+    const f = await createMultisig();
+    const outsider = newWallet();
+    const proposal = await nextProposal(f.multisig);
+    const expiresAt = await expiresIn(3600);
+    const accounts = {
+      multisig: f.multisig,
+      proposal,
+      proposer: outsider.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    await expectError(
+      program.methods
+        .createProposal(new BN(1), expiresAt)
+        .accountsStrict({ ...accounts, vault: f.vault, recipient: outsider.publicKey })
+        .signers([outsider])
+        .rpc(),
+      "Unauthorized"
+    );
+    const ix = SystemProgram.transfer({ fromPubkey: f.vault, toPubkey: outsider.publicKey, lamports: 1 });
+    const stored = [{ programId: ix.programId, accounts: ix.keys, data: ix.data }];
+    await expectError(
+      program.methods
+        .createTransactionProposal(stored, expiresAt)
+        .accountsStrict(accounts)
+        .signers([outsider])
+        .rpc(),
+      "Unauthorized"
+    );
+    await expectError(
+      program.methods
+        .createConfigProposal({ changeThreshold: { threshold: 1 } }, expiresAt)
+        .accountsStrict(accounts)
+        .signers([outsider])
+        .rpc(),
+      "Unauthorized"
+    );
+
+    const { transactionCount } = await program.account.multisig.fetch(f.multisig);
+    expect(transactionCount.toNumber()).to.equal(0);
+    expect(await exists(proposal)).to.equal(false);
+  });
+
+  it("keeps the vault rent-exempt when executing a transfer", async () => {
+    const f = await createMultisig();
+    await deposit(f, f.owners[0], 1_000_000);
+    const recipient = Keypair.generate().publicKey;
+    const overdraft = await createProposal(f, recipient, 1_000_000_000);
+    const intoRent = await createProposal(f, recipient, 1_000_001);
+    await approveToThreshold(f, overdraft);
+    await approveToThreshold(f, intoRent);
+
+    await expectError(
+      execute({ multisig: f.multisig, vault: f.vault, proposal: overdraft, recipient }),
+      "InsufficientFunds"
+    );
+    await expectError(
+      execute({ multisig: f.multisig, vault: f.vault, proposal: intoRent, recipient }),
+      "BelowRentExempt"
+    );
+    // The failed payout reverts the Executed status written before it.
+    const { status } = await program.account.proposal.fetch(intoRent);
+    expect(status).to.deep.equal({ active: {} });
+  });
+
+  it("limits spending to the limit's owner and the vault's spare lamports", async () => {
+    const f = await createMultisig();
+    await deposit(f, f.owners[0], 1_000_000);
+    const spendingLimit = await createSpendingLimit(f, f.owners[1].publicKey, 5_000_000);
+    const recipient = Keypair.generate().publicKey;
+    const spend = (owner: Keypair, amount: number) =>
+      program.methods
+        .spendFromLimit(new BN(amount))
+        .accountsStrict({ multisig: f.multisig, vault: f.vault, spendingLimit, recipient, owner: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+    await expectError(spend(f.owners[0], 1_000_000), "Unauthorized");
+    // Within the limit, but more than the vault holds above its rent-exempt minimum.
+    await expectError(spend(f.owners[1], 1_000_001), "BelowRentExempt");
+    await spend(f.owners[1], 1_000_000);
+    expect(await lamports(recipient)).to.equal(1_000_000);
+    const { remaining } = await program.account.spendingLimit.fetch(spendingLimit);
+    expect(remaining.toNumber()).to.equal(4_000_000);
+  });
+});
+
+// The attacks above, replayed against tests/fixtures/unhardened.rs, the program as it was before
+// its account validation was hardened. Each one lands there.
+describe("multisig-treasury before account validation was hardened", () => {
+  let legacy: Program;
+
+  const legacyVault = (multisig: PublicKey) =>
+    PublicKey.findProgramAddressSync([Buffer.from("vault"), multisig.toBuffer()], legacy.programId)[0];
+
+  beforeEach(async () => {
+    program = await startTemplate("multisig_treasury");
+    legacy = loadProgram("multisig_treasury_unhardened");
+  });
+
+  async function createLegacyMultisig(ownerCount: number, threshold: number): Promise<MultisigFixture> {
+    const owners = Array.from({ length: ownerCount }, () => newWallet());
+    const multisig = Keypair.generate();
+    const vault = legacyVault(multisig.publicKey);
+    await legacy.methods
+      .initializeMultisig(owners.map((o) => o.publicKey), threshold)
+      .accountsStrict({
+        multisig: multisig.publicKey,
+        vault,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([multisig])
+      .rpc();
+    return { owners, multisig: multisig.publicKey, vault };
+  }
+
+  // The unhardened deposit cannot move lamports, so vaults are funded with a plain transfer.
+  async function fundVault(vault: PublicKey, amount: number) {
+    await send([SystemProgram.transfer({ fromPubkey: provider.wallet.publicKey, toPubkey: vault, lamports: amount })]);
+  }
+
+  // Any signer may propose, at any keypair address.
+  async function createLegacyProposal(
+    f: MultisigFixture,
+    proposer: Keypair,
+    recipient: PublicKey,
+    amount: number
+  ): Promise<PublicKey> {
+    const proposal = Keypair.generate();
+    await legacy.methods
+      .createProposal(new BN(amount))
+      .accountsStrict({
+        multisig: f.multisig,
+        vault: f.vault,
+        proposal: proposal.publicKey,
+        recipient,
+        proposer: proposer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([proposer, proposal])
+      .rpc();
+    return proposal.publicKey;
+  }
+
+  function legacyApprove(multisig: PublicKey, proposal: PublicKey, owner: Keypair) {
+    return legacy.methods
+      .approve()
+      .accountsStrict({ multisig, proposal, owner: owner.publicKey })
+      .signers([owner])
+      .rpc();
+  }
+
+  function legacyExecute(accounts: { multisig: PublicKey; vault: PublicKey; proposal: PublicKey; recipient: PublicKey }) {
+    return legacy.methods.execute().accountsStrict(accounts).rpc();
+  }
+
+  it("pays out of another multisig's vault on execute", async () => {
+    const attacker = await createLegacyMultisig(1, 1);
+    const victim = await createLegacyMultisig(3, 2);
+    await fundVault(victim.vault, 1_000_000);
+    const before = await lamports(victim.vault);
+    const recipient = Keypair.generate().publicKey;
+    const proposal = await createLegacyProposal(attacker, attacker.owners[0], recipient, 1_000_000);
+    await legacyApprove(attacker.multisig, proposal, attacker.owners[0]);
+
+    await legacyExecute({ multisig: attacker.multisig, vault: victim.vault, proposal, recipient });
+    expect(await lamports(recipient)).to.equal(1_000_000);
+    expect(await lamports(victim.vault)).to.equal(before - 1_000_000);
+  });
+
+  it("counts approvals from another multisig's owners", async () => {
+    const victim = await createLegacyMultisig(3, 2);
+    const attacker = await createLegacyMultisig(2, 1);
+    await fundVault(victim.vault, 1_000_000);
+    const recipient = Keypair.generate().publicKey;
+    const proposal = await createLegacyProposal(victim, attacker.owners[0], recipient, 1_000_000);
+
+    // Each attacker owner's index in its own multisig fills a slot in the victim's approvals.
+    await legacyApprove(attacker.multisig, proposal, attacker.owners[0]);
+    await legacyApprove(attacker.multisig, proposal, attacker.owners[1]);
+    await legacyExecute({ multisig: victim.multisig, vault: victim.vault, proposal, recipient });
+    expect(await lamports(recipient)).to.equal(1_000_000);
+  });
+
+  it("creates proposals at arbitrary keypair addresses", async () => {
+    const f = await createLegacyMultisig(3, 2);
+    const recipient = Keypair.generate().publicKey;
+    const first = await createLegacyProposal(f, f.owners[0], recipient, 1);
+    const second = await createLegacyProposal(f, f.owners[0], recipient, 1);
+
+    // Nothing ties either address to the multisig, so proposals cannot be enumerated from it.
+    expect(await exists(first)).to.equal(true);
+    expect(await exists(second)).to.equal(true);
+  });
+
+  it("cannot take deposits into the read-only vault", async () => {
+    const f = await createLegacyMultisig(3, 2);
+    const depositor = f.owners[0];
+    await expectError(
+      legacy.methods
+        .deposit(new BN(1_000_000))
+        .accountsStrict({
+          multisig: f.multisig,
+          vault: f.vault,
+          depositor: depositor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([depositor])
+        .rpc(),
+      "read-only account"
+    );
+  });
+});
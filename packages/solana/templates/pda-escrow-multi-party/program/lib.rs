@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
 declare_id!("EscrowMulti111111111111111111111111111111");
 
@@ -6,28 +7,193 @@ declare_id!("EscrowMulti111111111111111111111111111111");
 pub mod pda_escrow_multi_party {
     use super::*;
 
-    pub fn create(ctx: Context<CreateEscrow>, amount: u64) -> Result<()> {
+    pub fn create(
+        ctx: Context<CreateEscrow>,
+        milestones: Vec<MilestoneTerms>,
+        arbiter_fee_bps: u16,
+    ) -> Result<()> {
+        require!(
+            !milestones.is_empty() && milestones.len() <= MAX_MILESTONES,
+            ErrorCode::InvalidMilestones
+        );
+        require!(arbiter_fee_bps <= MAX_ARBITER_FEE_BPS, ErrorCode::FeeTooHigh);
+        let buyer = ctx.accounts.buyer.key();
+        let seller = ctx.accounts.seller.key();
+        let arbiter = ctx.accounts.arbiter.key();
+        require!(
+            buyer != seller && arbiter != buyer && arbiter != seller,
+            ErrorCode::InvalidParty
+        );
+        let mut amount: u64 = 0;
+        for terms in milestones.iter() {
+            require!(terms.amount > 0, ErrorCode::InvalidAmount);
+            amount = amount.checked_add(terms.amount).ok_or(ErrorCode::MathOverflow)?;
+        }
+
         let escrow = &mut ctx.accounts.escrow;
-        escrow.buyer = ctx.accounts.buyer.key();
-        escrow.seller = ctx.accounts.seller.key();
-        escrow.arbiter = ctx.accounts.arbiter.key();
+        escrow.buyer = buyer;
+        escrow.seller = seller;
+        escrow.arbiter = arbiter;
         escrow.amount = amount;
+        escrow.arbiter_fee_bps = arbiter_fee_bps;
+        escrow.milestones = milestones
+            .into_iter()
+            .map(|terms| Milestone {
+                amount: terms.amount,
+                description_hash: terms.description_hash,
+                status: MilestoneStatus::Pending,
+            })
+            .collect();
         escrow.bump = ctx.bumps.escrow;
-        **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? += amount;
-        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? -= amount;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
         msg!("Escrow funded by buyer");
         Ok(())
     }
 
-    pub fn release(ctx: Context<ReleaseEscrow>) -> Result<()> {
-        require!(ctx.accounts.arbiter.key() == ctx.accounts.escrow.arbiter, ErrorCode::Unauthorized);
-        let amount = ctx.accounts.escrow.amount;
-        **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += amount;
-        ctx.accounts.escrow.amount = 0;
-        msg!("Escrow released to seller");
+    pub fn release(ctx: Context<ReleaseEscrow>, milestone: u8) -> Result<()> {
+        let amount = ctx.accounts.escrow.disputed_amount(milestone)?;
+        let fee = bps_of(amount, ctx.accounts.escrow.arbiter_fee_bps)?;
+        let to_seller = amount.checked_sub(fee).ok_or(ErrorCode::MathOverflow)?;
+
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        pay(&escrow_info, &ctx.accounts.arbiter.to_account_info(), fee)?;
+        pay(&escrow_info, &ctx.accounts.seller.to_account_info(), to_seller)?;
+        msg!("Arbiter released milestone {} to seller", milestone);
+
+        settle_milestone(
+            &mut ctx.accounts.escrow,
+            &ctx.accounts.buyer.to_account_info(),
+            milestone,
+            MilestoneStatus::Paid,
+        )
+    }
+
+    pub fn confirm_delivery(ctx: Context<ConfirmDelivery>, milestone: u8) -> Result<()> {
+        // A dispute freezes only its own milestone; the others can still be approved.
+        let amount = ctx.accounts.escrow.pending_amount(milestone)?;
+        pay(
+            &ctx.accounts.escrow.to_account_info(),
+            &ctx.accounts.seller.to_account_info(),
+            amount,
+        )?;
+        msg!("Buyer approved milestone {}, {} lamports paid to seller", milestone, amount);
+
+        settle_milestone(
+            &mut ctx.accounts.escrow,
+            &ctx.accounts.buyer.to_account_info(),
+            milestone,
+            MilestoneStatus::Paid,
+        )
+    }
+
+    pub fn open_dispute(ctx: Context<OpenDispute>, milestone: u8) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let party = ctx.accounts.party.key();
+        require!(
+            party == escrow.buyer || party == escrow.seller,
+            ErrorCode::Unauthorized
+        );
+        escrow.pending_amount(milestone)?;
+        escrow.milestones[milestone as usize].status = MilestoneStatus::Disputed;
+        msg!("Dispute opened on milestone {} by {}", milestone, party);
         Ok(())
     }
+
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        milestone: u8,
+        resolution: Resolution,
+    ) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        let amount = escrow.disputed_amount(milestone)?;
+        // A split pays the fee off the top; a refund returns the whole milestone to the buyer.
+        let (fee, to_seller, outcome) = match resolution {
+            Resolution::Split { seller_bps } => {
+                require!(seller_bps <= BPS_DENOMINATOR, ErrorCode::InvalidBps);
+                let fee = bps_of(amount, escrow.arbiter_fee_bps)?;
+                let remaining = amount.checked_sub(fee).ok_or(ErrorCode::MathOverflow)?;
+                (fee, bps_of(remaining, seller_bps)?, MilestoneStatus::Split)
+            }
+            Resolution::RefundBuyer => (0, 0, MilestoneStatus::Refunded),
+        };
+        let to_buyer = amount
+            .checked_sub(fee)
+            .and_then(|x| x.checked_sub(to_seller))
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        pay(&escrow_info, &ctx.accounts.arbiter.to_account_info(), fee)?;
+        pay(&escrow_info, &ctx.accounts.seller.to_account_info(), to_seller)?;
+        pay(&escrow_info, &ctx.accounts.buyer.to_account_info(), to_buyer)?;
+        msg!(
+            "Milestone {} resolved: seller {}, buyer {}, arbiter fee {}",
+            milestone,
+            to_seller,
+            to_buyer,
+            fee
+        );
+
+        settle_milestone(
+            &mut ctx.accounts.escrow,
+            &ctx.accounts.buyer.to_account_info(),
+            milestone,
+            outcome,
+        )
+    }
+}
+
+pub const MAX_MILESTONES: usize = 8;
+pub const BPS_DENOMINATOR: u16 = 10_000;
+pub const MAX_ARBITER_FEE_BPS: u16 = 1_000;
+
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let share = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / BPS_DENOMINATOR as u128;
+    u64::try_from(share).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+// Once nothing is pending the remaining rent goes back to the buyer who paid it.
+fn settle_milestone<'info>(
+    escrow: &mut Account<'info, Escrow>,
+    buyer: &AccountInfo<'info>,
+    index: u8,
+    outcome: MilestoneStatus,
+) -> Result<()> {
+    escrow.settle(index, outcome)?;
+    if escrow.amount == 0 {
+        escrow.close(buyer.clone())?;
+        msg!("All milestones settled, escrow closed");
+    }
+    Ok(())
+}
+
+// The escrow PDA is owned by this program, so it can be debited directly.
+fn pay(escrow: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let remaining = escrow
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let credited = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    **escrow.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? = credited;
+    Ok(())
 }
 
 #[derive(Accounts)]
@@ -42,8 +208,9 @@ pub struct CreateEscrow<'info> {
     pub escrow: Account<'info, Escrow>,
     #[account(mut)]
     pub buyer: Signer<'info>,
-    pub seller: SystemAccount<'info>,
-    pub arbiter: SystemAccount<'info>,
+    // Seller and arbiter agree to the milestones and the fee by co-signing.
+    pub seller: Signer<'info>,
+    pub arbiter: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
@@ -52,12 +219,63 @@ pub struct ReleaseEscrow<'info> {
     #[account(
         mut,
         seeds = [b"escrow", buyer.key().as_ref(), seller.key().as_ref()],
+        bump = escrow.bump,
+        has_one = buyer @ ErrorCode::InvalidParty,
+        has_one = seller @ ErrorCode::InvalidParty,
+        has_one = arbiter @ ErrorCode::Unauthorized
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    pub buyer: SystemAccount<'info>,
+    #[account(mut)]
+    pub seller: SystemAccount<'info>,
+    #[account(mut)]
+    pub arbiter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmDelivery<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", buyer.key().as_ref(), seller.key().as_ref()],
+        bump = escrow.bump,
+        has_one = buyer @ ErrorCode::Unauthorized,
+        has_one = seller @ ErrorCode::InvalidParty
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(mut)]
+    pub seller: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.seller.as_ref()],
         bump = escrow.bump
     )]
     pub escrow: Account<'info, Escrow>,
+    pub party: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", buyer.key().as_ref(), seller.key().as_ref()],
+        bump = escrow.bump,
+        has_one = buyer @ ErrorCode::InvalidParty,
+        has_one = seller @ ErrorCode::InvalidParty,
+        has_one = arbiter @ ErrorCode::Unauthorized
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut)]
     pub buyer: SystemAccount<'info>,
     #[account(mut)]
     pub seller: SystemAccount<'info>,
+    #[account(mut)]
     pub arbiter: Signer<'info>,
 }
 
@@ -67,15 +285,110 @@ pub struct Escrow {
     pub seller: Pubkey,
     pub arbiter: Pubkey,
     pub amount: u64,
+    pub arbiter_fee_bps: u16,
+    pub milestones: Vec<Milestone>,
     pub bump: u8,
 }
 
 impl Escrow {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 1;
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 2 + (4 + Milestone::LEN * MAX_MILESTONES) + 1;
+
+    fn milestone(&self, index: u8) -> Result<&Milestone> {
+        let milestone = self
+            .milestones
+            .get(index as usize)
+            .ok_or(ErrorCode::InvalidMilestone)?;
+        Ok(milestone)
+    }
+
+    // Amount of a milestone the buyer can still approve or dispute.
+    pub fn pending_amount(&self, index: u8) -> Result<u64> {
+        let milestone = self.milestone(index)?;
+        match milestone.status {
+            MilestoneStatus::Pending => Ok(milestone.amount),
+            MilestoneStatus::Disputed => err!(ErrorCode::MilestoneDisputed),
+            _ => err!(ErrorCode::MilestoneSettled),
+        }
+    }
+
+    // Amount of a milestone the arbiter can release or resolve.
+    pub fn disputed_amount(&self, index: u8) -> Result<u64> {
+        let milestone = self.milestone(index)?;
+        match milestone.status {
+            MilestoneStatus::Disputed => Ok(milestone.amount),
+            MilestoneStatus::Pending => err!(ErrorCode::NotDisputed),
+            _ => err!(ErrorCode::MilestoneSettled),
+        }
+    }
+
+    // `amount` is the balance still held for unsettled milestones.
+    pub fn settle(&mut self, index: u8, outcome: MilestoneStatus) -> Result<()> {
+        let milestone = self.milestone(index)?;
+        require!(
+            matches!(milestone.status, MilestoneStatus::Pending | MilestoneStatus::Disputed),
+            ErrorCode::MilestoneSettled
+        );
+        let amount = milestone.amount;
+        self.milestones[index as usize].status = outcome;
+        self.amount = self.amount.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MilestoneTerms {
+    pub amount: u64,
+    pub description_hash: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Milestone {
+    pub amount: u64,
+    pub description_hash: [u8; 32],
+    pub status: MilestoneStatus,
+}
+
+impl Milestone {
+    pub const LEN: usize = 8 + 32 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MilestoneStatus {
+    Pending,
+    Disputed,
+    Paid,
+    Split,
+    Refunded,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Split { seller_bps: u16 },
+    RefundBuyer,
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Buyer, seller and arbiter must be different and match the escrow")]
+    InvalidParty,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Basis points cannot exceed 10000")]
+    InvalidBps,
+    #[msg("Arbiter fee cannot exceed 1000 basis points")]
+    FeeTooHigh,
+    #[msg("Milestone is under dispute")]
+    MilestoneDisputed,
+    #[msg("Milestone is not disputed")]
+    NotDisputed,
+    #[msg("Escrow needs between 1 and 8 milestones")]
+    InvalidMilestones,
+    #[msg("Milestone index out of range")]
+    InvalidMilestone,
+    #[msg("Milestone is already settled")]
+    MilestoneSettled,
+    #[msg("Math overflow")]
+    MathOverflow,
 }
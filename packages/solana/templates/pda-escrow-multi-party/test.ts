@@ -1,17 +1,23 @@
 const INSTRUCTIONS = [
-  { name: "create", args: [{ name: "amount", type: "u64" }] },
-  { name: "release", args: [] },
+  { name: "create", args: [{ name: "milestones", type: '{"vec":{"defined":"MilestoneTerms"}}' }, { name: "arbiter_fee_bps", type: "u16" }] },
+  { name: "release", args: [{ name: "milestone", type: "u8" }] },
+  { name: "confirm_delivery", args: [{ name: "milestone", type: "u8" }] },
+  { name: "open_dispute", args: [{ name: "milestone", type: "u8" }] },
+  { name: "resolve_dispute", args: [{ name: "milestone", type: "u8" }, { name: "resolution", type: '{"defined":"Resolution"}' }] },
 ];
 
 function norm(name) { return String(name || "").replace(/[_-]/g, "").toLowerCase(); }
 function snakeToCamel(name) { return String(name).replace(/_([a-z])/g, function (_, c) { return c.toUpperCase(); }); }
 function typeToString(type) { if (typeof type === "string") return type; return JSON.stringify(type); }
 function findInstruction(idl, name) { const xs = Array.isArray(idl && idl.instructions) ? idl.instructions : []; return xs.find(function (x) { return norm(x.name) === norm(name); }); }
+function findAccount(ix, name) { return (ix.accounts || []).find(function (a) { return norm(a.name) === norm(name); }); }
+function findType(idl, name) { const xs = Array.isArray(idl && idl.types) ? idl.types : []; return xs.find(function (x) { return norm(x.name) === norm(name); }); }
+function variantNames(idl, name) { const t = findType(idl, name); return ((t && t.type && t.type.variants) || []).map(function (v) { return norm(v.name); }); }
 
 describe("pda-escrow-multi-party behavior", () => {
   it("injects playground runtime context", () => { expect(program).to.exist; expect(provider).to.exist; });
 
-  it("matches create/release signatures", () => {
+  it("matches create/release/confirm_delivery/open_dispute/resolve_dispute signatures", () => {
     INSTRUCTIONS.forEach(function (e) {
       const ix = findInstruction(program.idl, e.name);
       expect(ix).to.exist;
@@ -24,10 +30,13 @@ describe("pda-escrow-multi-party behavior", () => {
     });
   });
 
-  it("exposes create/release builders", () => {
+  it("exposes escrow lifecycle builders", () => {
     const m = program.methods || {};
-    expect(m[snakeToCamel("create")](1).rpc).to.be.a("function");
-    expect(m[snakeToCamel("release")]().rpc).to.be.a("function");
+    expect(m[snakeToCamel("create")]([{ amount: 1, descriptionHash: new Array(32).fill(0) }], 100).rpc).to.be.a("function");
+    expect(m[snakeToCamel("release")](0).rpc).to.be.a("function");
+    expect(m[snakeToCamel("confirm_delivery")](0).rpc).to.be.a("function");
+    expect(m[snakeToCamel("open_dispute")](0).rpc).to.be.a("function");
+    expect(m[snakeToCamel("resolve_dispute")](0, { split: { sellerBps: 5000 } }).rpc).to.be.a("function");
   });
 
   it("exposes Escrow schema", () => {
@@ -37,6 +46,35 @@ describe("pda-escrow-multi-party behavior", () => {
     const fields = Array.isArray(e.type && e.type.fields) ? e.type.fields : [];
     const map = Object.fromEntries(fields.map(function (f) { return [norm(f.name), typeToString(f.type)]; }));
     expect(map[norm("amount")]).to.equal("u64");
+    expect(map[norm("arbiter_fee_bps")]).to.equal("u16");
+    expect(map[norm("milestones")]).to.equal('{"vec":{"defined":"Milestone"}}');
     expect(map[norm("bump")]).to.equal("u8");
   });
+
+  it("models per-milestone disputes with split or refund resolutions", () => {
+    expect(findType(program.idl, "EscrowStatus"), "disputes belong to milestones, not the escrow").to.not.exist;
+    expect(variantNames(program.idl, "Resolution")).to.deep.equal(["split", "refundbuyer"]);
+    const errors = (program.idl.errors || []).map(function (x) { return norm(x.name); });
+    expect(errors).to.include.members(["invalidparty", "invalidbps", "feetoohigh", "milestonedisputed", "notdisputed"]);
+  });
+
+  it("settles each milestone once", () => {
+    expect(variantNames(program.idl, "MilestoneStatus")).to.deep.equal(["pending", "disputed", "paid", "split", "refunded"]);
+    const errors = (program.idl.errors || []).map(function (x) { return norm(x.name); });
+    expect(errors).to.include.members(["invalidmilestones", "invalidmilestone", "milestonesettled"]);
+    expect(findAccount(findInstruction(program.idl, "release"), "buyer").isMut, "buyer receives rent on close").to.equal(true);
+    expect(findAccount(findInstruction(program.idl, "confirm_delivery"), "buyer").isMut).to.equal(true);
+  });
+
+  it("gives each step to the right signer", () => {
+    const create = findInstruction(program.idl, "create");
+    ["buyer", "seller", "arbiter"].forEach(function (name) {
+      expect(findAccount(create, name).isSigner, name + " must sign create").to.equal(true);
+    });
+    expect(findAccount(findInstruction(program.idl, "confirm_delivery"), "buyer").isSigner).to.equal(true);
+    expect(findAccount(findInstruction(program.idl, "open_dispute"), "party").isSigner).to.equal(true);
+    const resolve = findInstruction(program.idl, "resolve_dispute");
+    expect(findAccount(resolve, "arbiter").isSigner).to.equal(true);
+    expect(findAccount(resolve, "arbiter").isMut, "arbiter receives the fee").to.equal(true);
+  });
 });
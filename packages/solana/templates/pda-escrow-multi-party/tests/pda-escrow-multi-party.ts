@@ -0,0 +1,177 @@
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, Transaction } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  exists,
+  expectError,
+  lamports,
+  newWallet,
+  provider,
+  startTemplate,
+} from "../../../template-tests/bankrun";
+
+const MILESTONE = 1_000_000;
+const FEE_BPS = 500;
+
+interface EscrowFixture {
+  buyer: Keypair;
+  seller: Keypair;
+  arbiter: Keypair;
+  escrow: PublicKey;
+}
+
+let program: Program;
+
+const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+const escrowPda = (buyer: PublicKey, seller: PublicKey) => pda(Buffer.from("escrow"), buyer.toBuffer(), seller.toBuffer());
+
+function milestones(count: number) {
+  return Array.from({ length: count }, (_, i) => ({ amount: new BN(MILESTONE), descriptionHash: new Array(32).fill(i) }));
+}
+
+function parties(): EscrowFixture {
+  const [buyer, seller, arbiter] = [newWallet(), newWallet(), newWallet()];
+  return { buyer, seller, arbiter, escrow: escrowPda(buyer.publicKey, seller.publicKey) };
+}
+
+function create(f: EscrowFixture, feeBps: number, count = 2) {
+  return program.methods
+    .create(milestones(count), feeBps)
+    .accountsStrict({
+      escrow: f.escrow,
+      buyer: f.buyer.publicKey,
+      seller: f.seller.publicKey,
+      arbiter: f.arbiter.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([f.buyer, f.seller, f.arbiter])
+    .rpc();
+}
+
+async function createEscrow(count = 2): Promise<EscrowFixture> {
+  const f = parties();
+  await create(f, FEE_BPS, count);
+  return f;
+}
+
+function openDispute(f: EscrowFixture, party: Keypair, milestone: number) {
+  return program.methods
+    .openDispute(milestone)
+    .accountsStrict({ escrow: f.escrow, party: party.publicKey })
+    .signers([party])
+    .rpc();
+}
+
+function confirmDelivery(f: EscrowFixture, milestone: number) {
+  return program.methods
+    .confirmDelivery(milestone)
+    .accountsStrict({ escrow: f.escrow, buyer: f.buyer.publicKey, seller: f.seller.publicKey })
+    .signers([f.buyer])
+    .rpc();
+}
+
+function release(f: EscrowFixture, milestone: number) {
+  return program.methods
+    .release(milestone)
+    .accountsStrict({
+      escrow: f.escrow,
+      buyer: f.buyer.publicKey,
+      seller: f.seller.publicKey,
+      arbiter: f.arbiter.publicKey,
+    })
+    .signers([f.arbiter])
+    .rpc();
+}
+
+function resolveDispute(f: EscrowFixture, milestone: number, resolution: any) {
+  return program.methods
+    .resolveDispute(milestone, resolution)
+    .accountsStrict({
+      escrow: f.escrow,
+      buyer: f.buyer.publicKey,
+      seller: f.seller.publicKey,
+      arbiter: f.arbiter.publicKey,
+    })
+    .signers([f.arbiter])
+    .rpc();
+}
+
+describe("pda-escrow-multi-party transactions", () => {
+  beforeEach(async () => {
+    program = await startTemplate("pda_escrow_multi_party");
+  });
+
+  it("caps the arbiter fee", async () => {
+    const f = parties();
+    await expectError(create(f, 1_001), "FeeTooHigh");
+    await create(f, 1_000);
+    const escrow = await program.account.escrow.fetch(f.escrow);
+    expect(escrow.arbiterFeeBps).to.equal(1_000);
+  });
+
+  it("requires the seller and arbiter to sign create", async () => {
+    const f = parties();
+    // The client will not send without every declared signer, so clear the flag on the instruction.
+    const withoutSignature = async (party: Keypair, cosigner: Keypair) => {
+      const ix = await program.methods
+        .create(milestones(1), FEE_BPS)
+        .accountsStrict({
+          escrow: f.escrow,
+          buyer: f.buyer.publicKey,
+          seller: f.seller.publicKey,
+          arbiter: f.arbiter.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .instruction();
+      ix.keys.find((meta) => meta.pubkey.equals(party.publicKey))!.isSigner = false;
+      return provider.sendAndConfirm(new Transaction().add(ix), [f.buyer, cosigner]);
+    };
+
+    await expectError(withoutSignature(f.seller, f.arbiter), "AccountNotSigner");
+    await expectError(withoutSignature(f.arbiter, f.seller), "AccountNotSigner");
+    expect(await exists(f.escrow)).to.equal(false);
+  });
+
+  it("refunds a disputed milestone to the buyer without a fee", async () => {
+    const f = await createEscrow();
+    const arbiterBefore = await lamports(f.arbiter.publicKey);
+    const buyerBefore = await lamports(f.buyer.publicKey);
+    await openDispute(f, f.buyer, 0);
+    await resolveDispute(f, 0, { refundBuyer: {} });
+
+    expect(await lamports(f.arbiter.publicKey)).to.equal(arbiterBefore);
+    expect(await lamports(f.buyer.publicKey)).to.equal(buyerBefore + MILESTONE);
+  });
+
+  it("takes the fee off the top of a split", async () => {
+    const f = await createEscrow();
+    const arbiterBefore = await lamports(f.arbiter.publicKey);
+    const sellerBefore = await lamports(f.seller.publicKey);
+    await openDispute(f, f.seller, 0);
+    await resolveDispute(f, 0, { split: { sellerBps: 5_000 } });
+
+    const fee = (MILESTONE * FEE_BPS) / 10_000;
+    expect(await lamports(f.arbiter.publicKey)).to.equal(arbiterBefore + fee);
+    expect(await lamports(f.seller.publicKey)).to.equal(sellerBefore + (MILESTONE - fee) / 2);
+  });
+
+  it("keeps a dispute on one milestone from blocking the others", async () => {
+    const f = await createEscrow(3);
+    await openDispute(f, f.seller, 0);
+
+    await confirmDelivery(f, 1);
+    await expectError(confirmDelivery(f, 0), "MilestoneDisputed");
+    await expectError(openDispute(f, f.buyer, 0), "MilestoneDisputed");
+    // The arbiter can only act on the disputed milestone.
+    await expectError(release(f, 2), "NotDisputed");
+    await expectError(resolveDispute(f, 1, { refundBuyer: {} }), "MilestoneSettled");
+
+    await release(f, 0);
+    const escrow = await program.account.escrow.fetch(f.escrow);
+    expect(escrow.milestones.map((m) => Object.keys(m.status)[0])).to.deep.equal(["paid", "paid", "pending"]);
+    expect(escrow.amount.toNumber()).to.equal(MILESTONE);
+
+    await confirmDelivery(f, 2);
+    expect(await exists(f.escrow)).to.equal(false);
+  });
+});
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
 
 declare_id!("PdaEscrow1111111111111111111111111111111");
 
@@ -6,71 +8,344 @@ declare_id!("PdaEscrow1111111111111111111111111111111");
 pub mod pda_escrow {
     use super::*;
 
-    pub fn create(ctx: Context<CreateEscrow>, amount: u64) -> Result<()> {
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit_amount: u64,
+        receive_amount: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require!(deposit_amount > 0 && receive_amount > 0, ErrorCode::InvalidAmount);
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > Clock::get()?.unix_timestamp,
+                ErrorCode::InvalidExpiry
+            );
+        }
+        require!(
+            ctx.accounts.mint_a.key() != ctx.accounts.mint_b.key(),
+            ErrorCode::SameMint
+        );
+
         let escrow = &mut ctx.accounts.escrow;
-        escrow.authority = ctx.accounts.authority.key();
-        escrow.amount = amount;
+        escrow.maker = ctx.accounts.maker.key();
+        escrow.mint_a = ctx.accounts.mint_a.key();
+        escrow.mint_b = ctx.accounts.mint_b.key();
+        escrow.seed = seed;
+        escrow.remaining_a = deposit_amount;
+        escrow.remaining_b = receive_amount;
+        escrow.expires_at = expires_at;
         escrow.bump = ctx.bumps.escrow;
-        **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? += amount;
-        **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? -= amount;
-        msg!("Escrow funded with {} lamports", amount);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.maker_token_a.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.maker.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            deposit_amount,
+        )?;
+        msg!("Escrow offers {} of mint A for {} of mint B", deposit_amount, receive_amount);
         Ok(())
     }
 
-    pub fn release(ctx: Context<ReleaseEscrow>) -> Result<()> {
+    pub fn take(ctx: Context<Take>, amount_a: u64) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
         require!(
-            ctx.accounts.authority.key() == ctx.accounts.escrow.authority,
-            ErrorCode::Unauthorized
+            !escrow.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::EscrowExpired
         );
-        let amount = ctx.accounts.escrow.amount;
-        **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? += amount;
-        ctx.accounts.escrow.amount = 0;
-        msg!("Escrow released");
+        require!(
+            amount_a > 0 && amount_a <= escrow.remaining_a,
+            ErrorCode::InvalidAmount
+        );
+        let amount_b = escrow.price_for(amount_a)?;
+
+        // The taker pays the maker first; the vault only releases token A once that succeeds.
+        let pay_accounts = Transfer {
+            from: ctx.accounts.taker_token_b.to_account_info(),
+            to: ctx.accounts.maker_token_b.to_account_info(),
+            authority: ctx.accounts.taker.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), pay_accounts),
+            amount_b,
+        )?;
+
+        let seed_bytes = escrow.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"escrow", escrow.maker.as_ref(), &seed_bytes, &[escrow.bump]]];
+        let release_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.taker_token_a.to_account_info(),
+            authority: escrow.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                release_accounts,
+                signer_seeds,
+            ),
+            amount_a,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.remaining_a = escrow
+            .remaining_a
+            .checked_sub(amount_a)
+            .ok_or(ErrorCode::MathOverflow)?;
+        escrow.remaining_b = escrow
+            .remaining_b
+            .checked_sub(amount_b)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!("Filled {} of mint A for {} of mint B", amount_a, amount_b);
+        Ok(())
+    }
+
+    pub fn cancel(ctx: Context<Cancel>) -> Result<()> {
+        // Also used after a full fill, when the vault is empty and only rent comes back.
+        let refund = refund_and_close_vault(
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            &ctx.accounts.maker_token_a,
+            &ctx.accounts.maker.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+        msg!("Escrow cancelled, {} of mint A returned", refund);
         Ok(())
     }
+
+    pub fn refund_expired(ctx: Context<RefundExpired>) -> Result<()> {
+        // Permissionless: funds can only go back to the maker, so anyone may crank it.
+        require!(
+            ctx.accounts.escrow.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::NotExpired
+        );
+        let refund = refund_and_close_vault(
+            &ctx.accounts.escrow,
+            &ctx.accounts.vault,
+            &ctx.accounts.maker_token_a,
+            &ctx.accounts.maker.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+        msg!("Expired escrow refunded {} of mint A to the maker", refund);
+        Ok(())
+    }
+}
+
+// Returns whatever token A is left to the maker and closes the vault; Anchor closes the escrow.
+fn refund_and_close_vault<'info>(
+    escrow: &Account<'info, Escrow>,
+    vault: &Account<'info, TokenAccount>,
+    maker_token_a: &Account<'info, TokenAccount>,
+    maker: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<u64> {
+    let seed_bytes = escrow.seed.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] =
+        &[&[b"escrow", escrow.maker.as_ref(), &seed_bytes, &[escrow.bump]]];
+
+    let refund = vault.amount;
+    if refund > 0 {
+        let refund_accounts = Transfer {
+            from: vault.to_account_info(),
+            to: maker_token_a.to_account_info(),
+            authority: escrow.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                refund_accounts,
+                signer_seeds,
+            ),
+            refund,
+        )?;
+    }
+
+    let close_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: maker.clone(),
+        authority: escrow.to_account_info(),
+    };
+    token::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        close_accounts,
+        signer_seeds,
+    ))?;
+    Ok(refund)
 }
 
 #[derive(Accounts)]
-pub struct CreateEscrow<'info> {
+#[instruction(seed: u64)]
+pub struct Make<'info> {
     #[account(
         init,
-        payer = authority,
+        payer = maker,
         space = 8 + Escrow::LEN,
-        seeds = [b"escrow", authority.key().as_ref()],
+        seeds = [b"escrow", maker.key().as_ref(), &seed.to_le_bytes()],
         bump
     )]
     pub escrow: Account<'info, Escrow>,
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint_a, token::authority = maker)]
+    pub maker_token_a: Account<'info, TokenAccount>,
+    pub mint_a: Account<'info, Mint>,
+    pub mint_b: Account<'info, Mint>,
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub maker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ReleaseEscrow<'info> {
+pub struct Take<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", authority.key().as_ref()],
-        bump = escrow.bump
+        seeds = [b"escrow", maker.key().as_ref(), &escrow.seed.to_le_bytes()],
+        bump = escrow.bump,
+        has_one = maker @ ErrorCode::Unauthorized,
+        has_one = mint_a @ ErrorCode::InvalidMint,
+        has_one = mint_b @ ErrorCode::InvalidMint
     )]
     pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint_a, token::authority = taker)]
+    pub taker_token_a: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint_b, token::authority = taker)]
+    pub taker_token_b: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker
+    )]
+    pub maker_token_b: Account<'info, TokenAccount>,
+    pub mint_a: Account<'info, Mint>,
+    pub mint_b: Account<'info, Mint>,
+    pub maker: SystemAccount<'info>,
+    pub taker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Cancel<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", maker.key().as_ref(), &escrow.seed.to_le_bytes()],
+        bump = escrow.bump,
+        has_one = maker @ ErrorCode::Unauthorized,
+        has_one = mint_a @ ErrorCode::InvalidMint,
+        close = maker
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint_a, token::authority = maker)]
+    pub maker_token_a: Account<'info, TokenAccount>,
+    pub mint_a: Account<'info, Mint>,
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpired<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", maker.key().as_ref(), &escrow.seed.to_le_bytes()],
+        bump = escrow.bump,
+        has_one = maker @ ErrorCode::Unauthorized,
+        has_one = mint_a @ ErrorCode::InvalidMint,
+        close = maker
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    // The refund always lands in the maker's own ATA, created by the caller if the maker
+    // has closed it, so a keeper cannot be blocked or pick another maker-owned account.
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker
+    )]
+    pub maker_token_a: Account<'info, TokenAccount>,
+    pub mint_a: Account<'info, Mint>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[account]
 pub struct Escrow {
-    pub authority: Pubkey,
-    pub amount: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub seed: u64,
+    pub remaining_a: u64,
+    pub remaining_b: u64,
+    pub expires_at: Option<i64>,
     pub bump: u8,
 }
 
 impl Escrow {
-    pub const LEN: usize = 32 + 8 + 1;
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + (1 + 8) + 1;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    // Token B owed for a fill, pro rata on what is left and rounded up for the maker.
+    pub fn price_for(&self, amount_a: u64) -> Result<u64> {
+        let owed = (amount_a as u128)
+            .checked_mul(self.remaining_b as u128)
+            .and_then(|x| x.checked_add(self.remaining_a as u128 - 1))
+            .and_then(|x| x.checked_div(self.remaining_a as u128))
+            .ok_or(ErrorCode::MathOverflow)?;
+        u64::try_from(owed).map_err(|_| ErrorCode::MathOverflow.into())
+    }
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Amount must be non-zero and within the remaining offer")]
+    InvalidAmount,
+    #[msg("Offered and requested mints must differ")]
+    SameMint,
+    #[msg("Mint does not match the escrow")]
+    InvalidMint,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Escrow has expired")]
+    EscrowExpired,
+    #[msg("Escrow has not expired")]
+    NotExpired,
+    #[msg("Math overflow")]
+    MathOverflow,
 }
@@ -1,19 +1,65 @@
 const INSTRUCTIONS = [
   {
-    name: "create",
-    args: [{ name: "amount", type: "u64" }],
+    name: "make",
+    args: [
+      { name: "seed", type: "u64" },
+      { name: "deposit_amount", type: "u64" },
+      { name: "receive_amount", type: "u64" },
+      { name: "expires_at", type: '{"option":"i64"}' },
+    ],
     accounts: [
       { name: "escrow", isMut: true, isSigner: false },
-      { name: "authority", isMut: true, isSigner: true },
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "maker_token_a", isMut: true, isSigner: false },
+      { name: "mint_a", isMut: false, isSigner: false },
+      { name: "mint_b", isMut: false, isSigner: false },
+      { name: "maker", isMut: true, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+      { name: "associated_token_program", isMut: false, isSigner: false },
       { name: "system_program", isMut: false, isSigner: false },
     ],
   },
   {
-    name: "release",
+    name: "take",
+    args: [{ name: "amount_a", type: "u64" }],
+    accounts: [
+      { name: "escrow", isMut: true, isSigner: false },
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "taker_token_a", isMut: true, isSigner: false },
+      { name: "taker_token_b", isMut: true, isSigner: false },
+      { name: "maker_token_b", isMut: true, isSigner: false },
+      { name: "mint_a", isMut: false, isSigner: false },
+      { name: "mint_b", isMut: false, isSigner: false },
+      { name: "maker", isMut: false, isSigner: false },
+      { name: "taker", isMut: false, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "cancel",
     args: [],
     accounts: [
       { name: "escrow", isMut: true, isSigner: false },
-      { name: "authority", isMut: true, isSigner: true },
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "maker_token_a", isMut: true, isSigner: false },
+      { name: "mint_a", isMut: false, isSigner: false },
+      { name: "maker", isMut: true, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "refund_expired",
+    args: [],
+    accounts: [
+      { name: "escrow", isMut: true, isSigner: false },
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "maker_token_a", isMut: true, isSigner: false },
+      { name: "mint_a", isMut: false, isSigner: false },
+      { name: "maker", isMut: true, isSigner: false },
+      { name: "caller", isMut: true, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+      { name: "associated_token_program", isMut: false, isSigner: false },
+      { name: "system_program", isMut: false, isSigner: false },
     ],
   },
 ];
@@ -64,7 +110,7 @@ describe("pda-escrow behavior", () => {
     expect(assert).to.be.an("object");
   });
 
-  it("matches create/release argument and account contracts", () => {
+  it("matches make/take/cancel/refund_expired argument and account contracts", () => {
     INSTRUCTIONS.forEach(function (expectedInstruction) {
       const instruction = findInstruction(program.idl, expectedInstruction.name);
       expect(instruction, "Instruction missing: " + expectedInstruction.name).to.exist;
@@ -88,19 +134,23 @@ describe("pda-escrow behavior", () => {
     });
   });
 
-  it("exposes builder APIs for create and release", () => {
+  it("exposes builder APIs for make, take and cancel", () => {
     const methods = program.methods || {};
 
-    const createBuilder = methods[snakeToCamel("create")](1);
-    expect(createBuilder && createBuilder.accounts).to.be.a("function");
-    expect(createBuilder && createBuilder.rpc).to.be.a("function");
+    const makeBuilder = methods[snakeToCamel("make")](1, 1000, 500, null);
+    expect(makeBuilder && makeBuilder.accounts).to.be.a("function");
+    expect(makeBuilder && makeBuilder.rpc).to.be.a("function");
 
-    const releaseBuilder = methods[snakeToCamel("release")]();
-    expect(releaseBuilder && releaseBuilder.accounts).to.be.a("function");
-    expect(releaseBuilder && releaseBuilder.rpc).to.be.a("function");
+    const takeBuilder = methods[snakeToCamel("take")](400);
+    expect(takeBuilder && takeBuilder.accounts).to.be.a("function");
+    expect(takeBuilder && takeBuilder.rpc).to.be.a("function");
+
+    const cancelBuilder = methods[snakeToCamel("cancel")]();
+    expect(cancelBuilder && cancelBuilder.accounts).to.be.a("function");
+    expect(cancelBuilder && cancelBuilder.rpc).to.be.a("function");
   });
 
-  it("exposes Escrow state with authority/amount/bump fields", () => {
+  it("exposes Escrow state with both mints and remaining amounts for partial fills", () => {
     const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
     const escrow = accounts.find(function (entry) {
       return norm(entry.name) === norm("Escrow");
@@ -114,10 +164,55 @@ describe("pda-escrow behavior", () => {
       })
     );
 
-    expect(
-      fieldMap[norm("authority")] === "pubkey" || fieldMap[norm("authority")] === "publicKey"
-    ).to.equal(true);
-    expect(fieldMap[norm("amount")]).to.equal("u64");
+    ["maker", "mint_a", "mint_b"].forEach(function (name) {
+      const type = fieldMap[norm(name)];
+      expect(type === "pubkey" || type === "publicKey", name + " should be a pubkey").to.equal(true);
+    });
+    expect(fieldMap[norm("seed")]).to.equal("u64");
+    expect(fieldMap[norm("remaining_a")]).to.equal("u64");
+    expect(fieldMap[norm("remaining_b")]).to.equal("u64");
     expect(fieldMap[norm("bump")]).to.equal("u8");
   });
+
+  it("only lets the stored maker cancel and rejects mismatched mints", () => {
+    const cancel = findInstruction(program.idl, "cancel");
+    expect(isSigner(findAccount(cancel, "maker")), "cancel must be signed by the maker").to.equal(true);
+
+    const take = findInstruction(program.idl, "take");
+    expect(isSigner(findAccount(take, "maker")), "take must not need the maker's signature").to.equal(false);
+
+    const errors = (program.idl.errors || []).map(function (entry) {
+      return norm(entry.name);
+    });
+    ["Unauthorized", "InvalidAmount", "SameMint", "InvalidMint"].forEach(function (name) {
+      expect(errors).to.include(norm(name));
+    });
+  });
+
+  it("stores an optional expiry and lets anyone refund expired escrows to the maker", () => {
+    const methods = program.methods || {};
+    const refundBuilder = methods[snakeToCamel("refund_expired")]();
+    expect(refundBuilder && refundBuilder.rpc).to.be.a("function");
+
+    const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
+    const escrow = accounts.find(function (entry) {
+      return norm(entry.name) === norm("Escrow");
+    });
+    const fields = Array.isArray(escrow && escrow.type && escrow.type.fields) ? escrow.type.fields : [];
+    const expiresAt = fields.find(function (field) {
+      return norm(field.name) === norm("expires_at");
+    });
+    expect(expiresAt, "Escrow should store expires_at").to.exist;
+    expect(typeToString(expiresAt.type)).to.equal('{"option":"i64"}');
+
+    const refund = findInstruction(program.idl, "refund_expired");
+    expect(isSigner(findAccount(refund, "maker")), "refund_expired must not need the maker's signature").to.equal(false);
+
+    const errors = (program.idl.errors || []).map(function (entry) {
+      return norm(entry.name);
+    });
+    ["InvalidExpiry", "EscrowExpired", "NotExpired"].forEach(function (name) {
+      expect(errors).to.include(norm(name));
+    });
+  });
 });
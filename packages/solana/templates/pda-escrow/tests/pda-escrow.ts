@@ -0,0 +1,195 @@
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  ACCOUNT_SIZE,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createCloseAccountInstruction,
+  createInitializeAccount3Instruction,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import {
+  context,
+  createMint,
+  exists,
+  expectError,
+  lamports,
+  mintTo,
+  newWallet,
+  now,
+  provider,
+  send,
+  startTemplate,
+  tokenBalance,
+  warp,
+} from "../../../template-tests/bankrun";
+
+const DEPOSIT = 1_000;
+const RECEIVE = 500;
+
+interface EscrowFixture {
+  maker: Keypair;
+  escrow: PublicKey;
+  vault: PublicKey;
+  mintA: PublicKey;
+  mintB: PublicKey;
+  makerTokenA: PublicKey;
+}
+
+let program: Program;
+
+const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+const escrowPda = (maker: PublicKey, seed: BN) => pda(Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8));
+
+// The maker escrows their whole token A balance, so their ATA is left empty.
+async function makeEscrow(expiresIn: number): Promise<EscrowFixture> {
+  const maker = newWallet();
+  const [mintA, mintB] = [await createMint(), await createMint()];
+  const makerTokenA = await mintTo(mintA, maker.publicKey, DEPOSIT);
+  const seed = new BN(1);
+  const escrow = escrowPda(maker.publicKey, seed);
+  const vault = getAssociatedTokenAddressSync(mintA, escrow, true);
+  const expiresAt = new BN(((await now()) + BigInt(expiresIn)).toString());
+  await program.methods
+    .make(seed, new BN(DEPOSIT), new BN(RECEIVE), expiresAt)
+    .accountsStrict({
+      escrow,
+      vault,
+      makerTokenA,
+      mintA,
+      mintB,
+      maker: maker.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([maker])
+    .rpc();
+  return { maker, escrow, vault, mintA, mintB, makerTokenA };
+}
+
+interface Taker {
+  taker: Keypair;
+  takerTokenA: PublicKey;
+  takerTokenB: PublicKey;
+}
+
+async function newTaker(f: EscrowFixture): Promise<Taker> {
+  const taker = newWallet();
+  const takerTokenA = await mintTo(f.mintA, taker.publicKey, 0);
+  const takerTokenB = await mintTo(f.mintB, taker.publicKey, RECEIVE);
+  return { taker, takerTokenA, takerTokenB };
+}
+
+function take(f: EscrowFixture, t: Taker, amountA: number) {
+  return program.methods
+    .take(new BN(amountA))
+    .accountsStrict({
+      escrow: f.escrow,
+      vault: f.vault,
+      takerTokenA: t.takerTokenA,
+      takerTokenB: t.takerTokenB,
+      makerTokenB: getAssociatedTokenAddressSync(f.mintB, f.maker.publicKey),
+      mintA: f.mintA,
+      mintB: f.mintB,
+      maker: f.maker.publicKey,
+      taker: t.taker.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([t.taker])
+    .rpc();
+}
+
+async function remaining(f: EscrowFixture) {
+  const { remainingA, remainingB } = await program.account.escrow.fetch(f.escrow);
+  return [remainingA.toNumber(), remainingB.toNumber()];
+}
+
+function refundExpired(f: EscrowFixture, caller: Keypair) {
+  return program.methods
+    .refundExpired()
+    .accountsStrict({
+      escrow: f.escrow,
+      vault: f.vault,
+      makerTokenA: f.makerTokenA,
+      mintA: f.mintA,
+      maker: f.maker.publicKey,
+      caller: caller.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([caller])
+    .rpc();
+}
+
+describe("pda-escrow transactions", () => {
+  beforeEach(async () => {
+    program = await startTemplate("pda_escrow");
+  });
+
+  it("prices partial fills pro rata on what is left, rounding up for the maker", async () => {
+    const f = await makeEscrow(60);
+    const makerTokenB = await mintTo(f.mintB, f.maker.publicKey, 0);
+    const t = await newTaker(f);
+
+    // 333 * 500 / 1000 = 166.5, so the maker is owed 167.
+    await take(f, t, 333);
+    expect(await remaining(f)).to.deep.equal([667, 333]);
+    expect(await tokenBalance(makerTokenB)).to.equal(BigInt(167));
+
+    // The next fill is priced on the remaining 667 for 333: 333 * 333 / 667 = 166.25 -> 167.
+    await take(f, t, 333);
+    expect(await remaining(f)).to.deep.equal([334, 166]);
+    expect(await tokenBalance(makerTokenB)).to.equal(BigInt(334));
+    await expectError(take(f, t, 335), "InvalidAmount");
+
+    // The last fill takes exactly what is left, so rounding never overcharges in total.
+    await take(f, t, 334);
+    expect(await remaining(f)).to.deep.equal([0, 0]);
+    expect(await tokenBalance(makerTokenB)).to.equal(BigInt(RECEIVE));
+    expect(await tokenBalance(t.takerTokenA)).to.equal(BigInt(DEPOSIT));
+    expect(await tokenBalance(t.takerTokenB)).to.equal(BigInt(0));
+    expect(await tokenBalance(f.vault)).to.equal(BigInt(0));
+    await expectError(take(f, t, 1), "InvalidAmount");
+  });
+
+  it("refunds into a recreated maker ATA after the maker closed it", async () => {
+    const f = await makeEscrow(60);
+    await send([createCloseAccountInstruction(f.makerTokenA, f.maker.publicKey, f.maker.publicKey)], [f.maker]);
+    expect(await exists(f.makerTokenA)).to.equal(false);
+
+    const keeper = newWallet();
+    await expectError(refundExpired(f, keeper), "NotExpired");
+    await warp(60);
+    await refundExpired(f, keeper);
+
+    expect(await tokenBalance(f.makerTokenA)).to.equal(BigInt(DEPOSIT));
+    expect(await exists(f.escrow)).to.equal(false);
+    // The keeper, not the maker, paid for the recreated account.
+    expect(await lamports(keeper.publicKey)).to.be.lessThan(10_000_000_000);
+  });
+
+  it("refuses a maker-owned token account other than the ATA", async () => {
+    const f = await makeEscrow(60);
+    const other = Keypair.generate();
+    const rent = await context.banksClient.getRent();
+    await send(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: provider.wallet.publicKey,
+          newAccountPubkey: other.publicKey,
+          lamports: Number(rent.minimumBalance(BigInt(ACCOUNT_SIZE))),
+          space: ACCOUNT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeAccount3Instruction(other.publicKey, f.mintA, f.maker.publicKey),
+      ],
+      [other]
+    );
+    await warp(60);
+
+    await expectError(refundExpired({ ...f, makerTokenA: other.publicKey }, f.maker), "AccountNotAssociatedTokenAccount");
+  });
+});
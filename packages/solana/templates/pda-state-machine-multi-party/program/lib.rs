@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+
+declare_id!("PdaStateMu1tiParty1111111111111111111111");
+
+#[program]
+pub mod pda_state_machine_multi_party {
+    use super::*;
+
+    pub fn create_order(ctx: Context<CreateOrder>, order_id: u64) -> Result<()> {
+        let buyer = ctx.accounts.buyer.key();
+        let seller = ctx.accounts.seller.key();
+        let inspector = ctx.accounts.inspector.key();
+        let arbiter = ctx.accounts.arbiter.key();
+        // Each key holds exactly one role so an approval can never count twice.
+        require!(
+            buyer != seller && buyer != inspector && seller != inspector,
+            ErrorCode::DuplicateParty
+        );
+        require!(
+            arbiter != buyer && arbiter != seller && arbiter != inspector,
+            ErrorCode::DuplicateParty
+        );
+
+        let order = &mut ctx.accounts.order;
+        order.buyer = buyer;
+        order.seller = seller;
+        order.inspector = inspector;
+        order.arbiter = arbiter;
+        order.order_id = order_id;
+        order.state = OrderState::Created;
+        order.disputed_from = OrderState::Created;
+        order.approvals = 0;
+        order.bump = ctx.bumps.order;
+        msg!("Order {} created", order_id);
+        Ok(())
+    }
+
+    pub fn approve(ctx: Context<OrderAction>) -> Result<()> {
+        let order = &mut ctx.accounts.order;
+        let role = order.role_of(ctx.accounts.party.key())?;
+        let (next, required) = order.state.next().ok_or(ErrorCode::InvalidState)?;
+        require!(required & role != 0, ErrorCode::RoleNotRequired);
+        require!(order.approvals & role == 0, ErrorCode::AlreadyApproved);
+
+        order.approvals |= role;
+        // The transition fires when the last required approval lands.
+        if order.approvals == required {
+            order.state = next;
+            order.approvals = 0;
+            msg!("Order moved to {:?}", next);
+        } else {
+            msg!("Approval recorded");
+        }
+        Ok(())
+    }
+
+    pub fn dispute(ctx: Context<OrderAction>) -> Result<()> {
+        let order = &mut ctx.accounts.order;
+        order.role_of(ctx.accounts.party.key())?;
+        require!(order.state.next().is_some(), ErrorCode::InvalidState);
+        order.disputed_from = order.state;
+        order.state = OrderState::Disputed;
+        order.approvals = 0;
+        msg!("Order disputed");
+        Ok(())
+    }
+
+    pub fn resolve(ctx: Context<Resolve>, resolution: Resolution) -> Result<()> {
+        let order = &mut ctx.accounts.order;
+        require!(order.state == OrderState::Disputed, ErrorCode::NotDisputed);
+        order.state = match resolution {
+            Resolution::Resume => order.disputed_from,
+            Resolution::Complete => OrderState::Completed,
+            Resolution::Cancel => OrderState::Cancelled,
+        };
+        msg!("Dispute resolved, order moved to {:?}", order.state);
+        Ok(())
+    }
+}
+
+pub const ROLE_BUYER: u8 = 1 << 0;
+pub const ROLE_SELLER: u8 = 1 << 1;
+pub const ROLE_INSPECTOR: u8 = 1 << 2;
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct CreateOrder<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + Order::LEN,
+        seeds = [b"order", buyer.key().as_ref(), &order_id.to_le_bytes()],
+        bump
+    )]
+    pub order: Account<'info, Order>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub seller: SystemAccount<'info>,
+    pub inspector: SystemAccount<'info>,
+    pub arbiter: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OrderAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump
+    )]
+    pub order: Account<'info, Order>,
+    pub party: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Resolve<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+        has_one = arbiter @ ErrorCode::Unauthorized
+    )]
+    pub order: Account<'info, Order>,
+    pub arbiter: Signer<'info>,
+}
+
+#[account]
+pub struct Order {
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub inspector: Pubkey,
+    pub arbiter: Pubkey,
+    pub order_id: u64,
+    pub state: OrderState,
+    pub disputed_from: OrderState,
+    pub approvals: u8,
+    pub bump: u8,
+}
+
+impl Order {
+    pub const LEN: usize = 32 * 4 + 8 + 1 + 1 + 1 + 1;
+
+    pub fn role_of(&self, key: Pubkey) -> Result<u8> {
+        if key == self.buyer {
+            Ok(ROLE_BUYER)
+        } else if key == self.seller {
+            Ok(ROLE_SELLER)
+        } else if key == self.inspector {
+            Ok(ROLE_INSPECTOR)
+        } else {
+            err!(ErrorCode::Unauthorized)
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrderState {
+    Created,
+    Confirmed,
+    Shipped,
+    Inspected,
+    Completed,
+    Disputed,
+    Cancelled,
+}
+
+impl OrderState {
+    // Next state and the roles that must all approve it; None once the order is settled.
+    pub fn next(self) -> Option<(OrderState, u8)> {
+        match self {
+            OrderState::Created => Some((OrderState::Confirmed, ROLE_BUYER | ROLE_SELLER)),
+            OrderState::Confirmed => Some((OrderState::Shipped, ROLE_SELLER)),
+            OrderState::Shipped => Some((OrderState::Inspected, ROLE_INSPECTOR)),
+            OrderState::Inspected => Some((OrderState::Completed, ROLE_BUYER | ROLE_INSPECTOR)),
+            OrderState::Completed | OrderState::Disputed | OrderState::Cancelled => None,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Resume,
+    Complete,
+    Cancel,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Buyer, seller, inspector and arbiter must be different keys")]
+    DuplicateParty,
+    #[msg("Order cannot move from its current state")]
+    InvalidState,
+    #[msg("Signer's role is not required for this transition")]
+    RoleNotRequired,
+    #[msg("Role already approved this transition")]
+    AlreadyApproved,
+    #[msg("Order is not disputed")]
+    NotDisputed,
+}
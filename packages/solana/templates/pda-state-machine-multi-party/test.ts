@@ -0,0 +1,69 @@
+const INSTRUCTIONS = [
+  { name: "create_order", args: ["order_id"] },
+  { name: "approve", args: [] },
+  { name: "dispute", args: [] },
+  { name: "resolve", args: ["resolution"] },
+];
+
+function norm(name) { return String(name || "").replace(/[_-]/g, "").toLowerCase(); }
+function snakeToCamel(name) { return String(name).replace(/_([a-z])/g, function (_, c) { return c.toUpperCase(); }); }
+function typeToString(type) { if (typeof type === "string") return type; if (type && type.defined) return "defined:" + type.defined; return JSON.stringify(type); }
+function findType(idl, name) { const xs = Array.isArray(idl && idl.types) ? idl.types : []; return xs.find(function (x) { return norm(x.name) === norm(name); }); }
+function variantNames(idl, name) { const t = findType(idl, name); return ((t && t.type && t.type.variants) || []).map(function (v) { return norm(v.name); }); }
+function errorNames(idl) { return (idl.errors || []).map(function (e) { return norm(e.name); }); }
+function findInstruction(idl, name) { const xs = Array.isArray(idl && idl.instructions) ? idl.instructions : []; return xs.find(function (x) { return norm(x.name) === norm(name); }); }
+function accountNames(ix) { return (ix.accounts || []).map(function (a) { return norm(a.name); }); }
+
+describe("pda-state-machine-multi-party behavior", () => {
+  it("injects playground runtime context", () => { expect(program).to.exist; expect(provider).to.exist; });
+
+  it("matches create_order/approve/dispute/resolve signatures", () => {
+    INSTRUCTIONS.forEach(function (e) {
+      const ix = findInstruction(program.idl, e.name);
+      expect(ix).to.exist;
+      const args = Array.isArray(ix.args) ? ix.args : [];
+      expect(args.map(function (a) { return norm(a.name); })).to.deep.equal(e.args.map(norm));
+    });
+    expect(typeToString(findInstruction(program.idl, "resolve").args[0].type)).to.equal("defined:Resolution");
+  });
+
+  it("exposes order lifecycle builders", () => {
+    const m = program.methods || {};
+    expect(m[snakeToCamel("create_order")](1).rpc).to.be.a("function");
+    expect(m[snakeToCamel("approve")]().rpc).to.be.a("function");
+    expect(m[snakeToCamel("dispute")]().rpc).to.be.a("function");
+    expect(m[snakeToCamel("resolve")]({ cancel: {} }).rpc).to.be.a("function");
+  });
+
+  it("exposes Order schema with an approval mask", () => {
+    const accs = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
+    const order = accs.find(function (x) { return norm(x.name) === norm("Order"); });
+    expect(order).to.exist;
+    const fields = Array.isArray(order.type && order.type.fields) ? order.type.fields : [];
+    const map = Object.fromEntries(fields.map(function (f) { return [norm(f.name), typeToString(f.type)]; }));
+    ["buyer", "seller", "inspector", "arbiter"].forEach(function (k) { expect(["pubkey", "publicKey"]).to.include(map[k]); });
+    expect(map[norm("order_id")]).to.equal("u64");
+    expect(map[norm("state")]).to.equal("defined:OrderState");
+    expect(map[norm("disputed_from")]).to.equal("defined:OrderState");
+    expect(map[norm("approvals")]).to.equal("u8");
+    expect(map[norm("bump")]).to.equal("u8");
+  });
+
+  it("models the lifecycle with a Disputed state and arbiter resolutions", () => {
+    expect(variantNames(program.idl, "OrderState")).to.deep.equal(["created", "confirmed", "shipped", "inspected", "completed", "disputed", "cancelled"]);
+    expect(variantNames(program.idl, "Resolution")).to.deep.equal(["resume", "complete", "cancel"]);
+  });
+
+  it("lets parties sign approvals and only the arbiter resolve", () => {
+    ["approve", "dispute"].forEach(function (name) {
+      const ix = findInstruction(program.idl, name);
+      expect(accountNames(ix)).to.deep.equal(["order", "party"]);
+      const party = ix.accounts.find(function (a) { return norm(a.name) === "party"; });
+      expect(party.isSigner, name + " must be signed by a party").to.equal(true);
+    });
+    const resolve = findInstruction(program.idl, "resolve");
+    const arbiter = resolve.accounts.find(function (a) { return norm(a.name) === "arbiter"; });
+    expect(arbiter && arbiter.isSigner, "resolve must be signed by the arbiter").to.equal(true);
+    expect(errorNames(program.idl)).to.include.members(["duplicateparty", "rolenotrequired", "alreadyapproved", "notdisputed", "invalidstate"]);
+  });
+});
@@ -6,34 +6,131 @@ declare_id!("PdaState11111111111111111111111111111111");
 pub mod pda_state_machine {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        state_count: u8,
+        roles: Vec<Pubkey>,
+        transitions: Vec<TransitionRule>,
+    ) -> Result<()> {
+        validate_table(state_count, &roles, &transitions)?;
         let machine = &mut ctx.accounts.machine;
         machine.authority = ctx.accounts.authority.key();
-        machine.state = MachineState::Idle;
+        machine.state = 0;
+        machine.state_count = state_count;
+        machine.entered_at = Clock::get()?.unix_timestamp;
+        machine.roles = roles;
+        machine.transitions = transitions;
         machine.bump = ctx.bumps.machine;
-        msg!("State machine initialized");
+
+        let mut history = ctx.accounts.history.load_init()?;
+        history.machine = machine.key();
+        msg!("State machine initialized with {} states", state_count);
         Ok(())
     }
 
-    pub fn advance(ctx: Context<Advance>) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.machine.authority,
-            ErrorCode::Unauthorized
-        );
-        ctx.accounts.machine.state = ctx.accounts.machine.state.next();
-        msg!("State advanced");
+    pub fn transition(ctx: Context<Transition>, to: u8) -> Result<()> {
+        let machine = &mut ctx.accounts.machine;
+        let signer = ctx.accounts.signer.key();
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let rule = machine.find_rule(to)?;
+
+        // A role guard hands the edge to that role; otherwise only the authority may move.
+        match rule.guard.role {
+            Some(role) => require!(
+                machine.roles[role as usize] == signer,
+                ErrorCode::MissingRole
+            ),
+            None => require!(signer == machine.authority, ErrorCode::Unauthorized),
+        }
+        let elapsed = now.checked_sub(machine.entered_at).ok_or(ErrorCode::MathOverflow)?;
+        require!(elapsed >= rule.guard.min_elapsed, ErrorCode::GuardTimeNotElapsed);
+
+        record(&ctx.accounts.history, machine.state, to, signer, &clock)?;
+        machine.state = to;
+        machine.entered_at = now;
+        msg!("State moved to {}", to);
         Ok(())
     }
 
-    pub fn reset(ctx: Context<Advance>) -> Result<()> {
+    pub fn reset(ctx: Context<Reset>) -> Result<()> {
         require!(
             ctx.accounts.authority.key() == ctx.accounts.machine.authority,
             ErrorCode::Unauthorized
         );
-        ctx.accounts.machine.state = MachineState::Idle;
+        let clock = Clock::get()?;
+        let from = ctx.accounts.machine.state;
+        record(&ctx.accounts.history, from, 0, ctx.accounts.authority.key(), &clock)?;
+        ctx.accounts.machine.state = 0;
+        ctx.accounts.machine.entered_at = clock.unix_timestamp;
         msg!("State reset");
         Ok(())
     }
+
+    pub fn recent_transitions(
+        ctx: Context<ReadHistory>,
+        count: u8,
+    ) -> Result<Vec<TransitionRecord>> {
+        require!(count as usize <= MAX_HISTORY_READ, ErrorCode::HistoryReadTooLarge);
+        let history = ctx.accounts.history.load()?;
+        Ok(history.recent(count as usize))
+    }
+}
+
+pub const MAX_STATES: u8 = 16;
+pub const MAX_ROLES: usize = 4;
+pub const MAX_TRANSITIONS: usize = 32;
+pub const HISTORY_LEN: usize = 32;
+// Return data is capped at 1024 bytes, so reads are limited to 16 records.
+pub const MAX_HISTORY_READ: usize = 16;
+
+fn record(
+    history: &AccountLoader<History>,
+    from: u8,
+    to: u8,
+    actor: Pubkey,
+    clock: &Clock,
+) -> Result<()> {
+    let mut history = history.load_mut()?;
+    history.push(TransitionRecord {
+        actor,
+        slot: clock.slot,
+        timestamp: clock.unix_timestamp,
+        from,
+        to,
+        _padding: [0; 6],
+    });
+    Ok(())
+}
+
+fn validate_table(
+    state_count: u8,
+    roles: &[Pubkey],
+    transitions: &[TransitionRule],
+) -> Result<()> {
+    require!(
+        state_count > 0 && state_count <= MAX_STATES,
+        ErrorCode::InvalidStateCount
+    );
+    require!(roles.len() <= MAX_ROLES, ErrorCode::TooManyRoles);
+    require!(transitions.len() <= MAX_TRANSITIONS, ErrorCode::TooManyTransitions);
+    for (i, rule) in transitions.iter().enumerate() {
+        require!(
+            rule.from < state_count && rule.to < state_count && rule.from != rule.to,
+            ErrorCode::InvalidTransitionRule
+        );
+        if let Some(role) = rule.guard.role {
+            require!((role as usize) < roles.len(), ErrorCode::InvalidRole);
+        }
+        require!(rule.guard.min_elapsed >= 0, ErrorCode::InvalidTransitionRule);
+        require!(
+            !transitions[..i]
+                .iter()
+                .any(|other| other.from == rule.from && other.to == rule.to),
+            ErrorCode::DuplicateTransition
+        );
+    }
+    Ok(())
 }
 
 #[derive(Accounts)]
@@ -46,52 +143,168 @@ pub struct Initialize<'info> {
         bump
     )]
     pub machine: Account<'info, Machine>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + History::LEN,
+        seeds = [b"history", machine.key().as_ref()],
+        bump
+    )]
+    pub history: AccountLoader<'info, History>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Advance<'info> {
+pub struct Transition<'info> {
+    #[account(
+        mut,
+        seeds = [b"machine", machine.authority.as_ref()],
+        bump = machine.bump
+    )]
+    pub machine: Account<'info, Machine>,
+    #[account(mut, seeds = [b"history", machine.key().as_ref()], bump)]
+    pub history: AccountLoader<'info, History>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Reset<'info> {
     #[account(
         mut,
         seeds = [b"machine", authority.key().as_ref()],
         bump = machine.bump
     )]
     pub machine: Account<'info, Machine>,
+    #[account(mut, seeds = [b"history", machine.key().as_ref()], bump)]
+    pub history: AccountLoader<'info, History>,
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ReadHistory<'info> {
+    pub machine: Account<'info, Machine>,
+    #[account(seeds = [b"history", machine.key().as_ref()], bump)]
+    pub history: AccountLoader<'info, History>,
+}
+
 #[account]
 pub struct Machine {
     pub authority: Pubkey,
-    pub state: MachineState,
+    pub state: u8,
+    pub state_count: u8,
+    pub entered_at: i64,
+    pub roles: Vec<Pubkey>,
+    pub transitions: Vec<TransitionRule>,
     pub bump: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum MachineState {
-    Idle,
-    Running,
-    Complete,
-}
-
-impl MachineState {
-    pub fn next(self) -> Self {
-        match self {
-            MachineState::Idle => MachineState::Running,
-            MachineState::Running => MachineState::Complete,
-            MachineState::Complete => MachineState::Complete,
-        }
+impl Machine {
+    // Space is reserved for the largest table so every machine has the same size.
+    pub const LEN: usize =
+        32 + 1 + 1 + 8 + (4 + 32 * MAX_ROLES) + (4 + TransitionRule::LEN * MAX_TRANSITIONS) + 1;
+
+    pub fn find_rule(&self, to: u8) -> Result<TransitionRule> {
+        self.transitions
+            .iter()
+            .find(|rule| rule.from == self.state && rule.to == to)
+            .copied()
+            .ok_or_else(|| ErrorCode::IllegalTransition.into())
     }
 }
 
-impl Machine {
-    pub const LEN: usize = 32 + 1 + 1;
+// Zero-copy: the ring buffer is read and written in place instead of being deserialized.
+#[account(zero_copy)]
+pub struct History {
+    pub machine: Pubkey,
+    pub count: u64,
+    pub entries: [TransitionRecord; HISTORY_LEN],
+}
+
+impl History {
+    pub const LEN: usize = 32 + 8 + TransitionRecord::LEN * HISTORY_LEN;
+
+    pub fn push(&mut self, record: TransitionRecord) {
+        let idx = (self.count % HISTORY_LEN as u64) as usize;
+        self.entries[idx] = record;
+        self.count += 1;
+    }
+
+    // Newest first; never returns more than the buffer has seen.
+    pub fn recent(&self, count: usize) -> Vec<TransitionRecord> {
+        let available = self.count.min(HISTORY_LEN as u64) as usize;
+        (1..=count.min(available))
+            .map(|back| {
+                let idx = ((self.count - back as u64) % HISTORY_LEN as u64) as usize;
+                self.entries[idx]
+            })
+            .collect()
+    }
+}
+
+// Plain borsh derives so recent_transitions can return records; zero_copy already supplies
+// the IDL definition that AnchorSerialize would duplicate.
+#[zero_copy]
+#[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+pub struct TransitionRecord {
+    pub actor: Pubkey,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub from: u8,
+    pub to: u8,
+    pub _padding: [u8; 6],
+}
+
+impl TransitionRecord {
+    pub const LEN: usize = 32 + 8 + 8 + 1 + 1 + 6;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionRule {
+    pub from: u8,
+    pub to: u8,
+    pub guard: Guard,
+}
+
+impl TransitionRule {
+    pub const LEN: usize = 1 + 1 + Guard::LEN;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Guard {
+    pub role: Option<u8>,
+    pub min_elapsed: i64,
+}
+
+impl Guard {
+    pub const LEN: usize = (1 + 1) + 8;
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("State count must be between 1 and 16")]
+    InvalidStateCount,
+    #[msg("Too many roles")]
+    TooManyRoles,
+    #[msg("Too many transitions")]
+    TooManyTransitions,
+    #[msg("Transition references an unknown state or a self-loop")]
+    InvalidTransitionRule,
+    #[msg("Guard references an unknown role")]
+    InvalidRole,
+    #[msg("Transition is listed twice")]
+    DuplicateTransition,
+    #[msg("No transition from the current state to the requested state")]
+    IllegalTransition,
+    #[msg("Signer does not hold the role this transition requires")]
+    MissingRole,
+    #[msg("Minimum time in the current state has not elapsed")]
+    GuardTimeNotElapsed,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Can read at most 16 history records at once")]
+    HistoryReadTooLarge,
 }
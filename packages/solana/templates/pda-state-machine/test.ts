@@ -1,31 +1,37 @@
 const INSTRUCTIONS = [
-  { name: "initialize", args: [] },
-  { name: "advance", args: [] },
+  { name: "initialize", args: ["state_count", "roles", "transitions"] },
+  { name: "transition", args: ["to"] },
   { name: "reset", args: [] },
+  { name: "recent_transitions", args: ["count"] },
 ];
 
 function norm(name) { return String(name || "").replace(/[_-]/g, "").toLowerCase(); }
 function snakeToCamel(name) { return String(name).replace(/_([a-z])/g, function (_, c) { return c.toUpperCase(); }); }
-function typeToString(type) { if (typeof type === "string") return type; if (type && type.defined) return "defined:" + type.defined; return JSON.stringify(type); }
+function typeToString(type) { if (typeof type === "string") return type; if (type && type.defined) return "defined:" + type.defined; if (type && type.vec) return "vec:" + typeToString(type.vec); if (type && type.option) return "option:" + typeToString(type.option); return JSON.stringify(type); }
+function findType(idl, name) { const xs = Array.isArray(idl && idl.types) ? idl.types : []; return xs.find(function (x) { return norm(x.name) === norm(name); }); }
+function errorNames(idl) { return (idl.errors || []).map(function (e) { return norm(e.name); }); }
 function findInstruction(idl, name) { const xs = Array.isArray(idl && idl.instructions) ? idl.instructions : []; return xs.find(function (x) { return norm(x.name) === norm(name); }); }
 
 describe("pda-state-machine behavior", () => {
   it("injects playground runtime context", () => { expect(program).to.exist; expect(provider).to.exist; });
 
-  it("matches initialize/advance/reset signatures", () => {
+  it("matches initialize/transition/reset signatures", () => {
     INSTRUCTIONS.forEach(function (e) {
       const ix = findInstruction(program.idl, e.name);
       expect(ix).to.exist;
       const args = Array.isArray(ix.args) ? ix.args : [];
-      expect(args.length).to.equal(0);
+      expect(args.map(function (a) { return norm(a.name); })).to.deep.equal(e.args.map(norm));
     });
+    expect(findInstruction(program.idl, "advance"), "hard-coded advance should be gone").to.not.exist;
   });
 
   it("exposes state machine builders", () => {
     const m = program.methods || {};
-    expect(m[snakeToCamel("initialize")]().rpc).to.be.a("function");
-    expect(m[snakeToCamel("advance")]().rpc).to.be.a("function");
+    const table = [{ from: 0, to: 1, guard: { role: null, minElapsed: 0 } }];
+    expect(m[snakeToCamel("initialize")](2, [], table).rpc).to.be.a("function");
+    expect(m[snakeToCamel("transition")](1).rpc).to.be.a("function");
     expect(m[snakeToCamel("reset")]().rpc).to.be.a("function");
+    expect(m[snakeToCamel("recent_transitions")](5).view).to.be.a("function");
   });
 
   it("exposes Machine schema", () => {
@@ -35,6 +41,45 @@ describe("pda-state-machine behavior", () => {
     const fields = Array.isArray(machine.type && machine.type.fields) ? machine.type.fields : [];
     const map = Object.fromEntries(fields.map(function (f) { return [norm(f.name), typeToString(f.type)]; }));
     expect(map[norm("bump")]).to.equal("u8");
-    expect(map[norm("state")] === "defined:MachineState" || map[norm("state")] === "machineState").to.equal(true);
+    expect(map[norm("state")]).to.equal("u8");
+    expect(map[norm("state_count")]).to.equal("u8");
+    expect(map[norm("entered_at")]).to.equal("i64");
+    expect(map[norm("transitions")]).to.equal("vec:defined:TransitionRule");
+  });
+
+  it("records transitions in a zero-copy ring buffer", () => {
+    const accs = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
+    const history = accs.find(function (x) { return norm(x.name) === norm("History"); });
+    expect(history, "History account missing").to.exist;
+    const fields = (history.type && history.type.fields) || [];
+    expect(fields.map(function (f) { return norm(f.name); })).to.deep.equal(["machine", "count", "entries"]);
+    const entries = fields.find(function (f) { return norm(f.name) === "entries"; });
+    expect(entries.type.array, "entries must be a fixed-size array").to.exist;
+    expect(entries.type.array[1]).to.equal(32);
+
+    const record = findType(program.idl, "TransitionRecord");
+    expect(record, "TransitionRecord type missing").to.exist;
+    expect(record.type.fields.map(function (f) { return norm(f.name); })).to.include.members(["from", "to", "actor", "slot", "timestamp"]);
+
+    ["initialize", "transition", "reset"].forEach(function (name) {
+      const ix = findInstruction(program.idl, name);
+      const acc = (ix.accounts || []).find(function (a) { return norm(a.name) === "history"; });
+      expect(acc, name + " must write the history account").to.exist;
+    });
+    const read = findInstruction(program.idl, "recent_transitions");
+    if (read.returns) expect(typeToString(read.returns)).to.equal("vec:defined:TransitionRecord");
+    expect(errorNames(program.idl)).to.include(norm("HistoryReadTooLarge"));
+  });
+
+  it("stores a transition table with role and time guards", () => {
+    const rule = findType(program.idl, "TransitionRule");
+    expect(rule, "TransitionRule type missing").to.exist;
+    expect(rule.type.fields.map(function (f) { return norm(f.name); })).to.deep.equal(["from", "to", "guard"]);
+    const guard = findType(program.idl, "Guard");
+    expect(guard, "Guard type missing").to.exist;
+    const guardFields = Object.fromEntries(guard.type.fields.map(function (f) { return [norm(f.name), typeToString(f.type)]; }));
+    expect(guardFields[norm("role")]).to.equal("option:u8");
+    expect(guardFields[norm("min_elapsed")]).to.equal("i64");
+    expect(errorNames(program.idl)).to.include.members(["IllegalTransition", "MissingRole", "GuardTimeNotElapsed", "InvalidTransitionRule", "DuplicateTransition"].map(norm));
   });
 });
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 declare_id!("PdaVault11111111111111111111111111111111");
 
@@ -6,17 +9,56 @@ declare_id!("PdaVault11111111111111111111111111111111");
 pub mod pda_vault {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        window_limit: u64,
+        window_duration: i64,
+        withdrawal_delay: i64,
+    ) -> Result<()> {
+        let guardian = ctx.accounts.guardian.key();
+        require!(guardian != ctx.accounts.authority.key(), ErrorCode::InvalidGuardian);
         let vault = &mut ctx.accounts.vault;
         vault.authority = ctx.accounts.authority.key();
         vault.bump = ctx.bumps.vault;
+        vault.guardian = guardian;
+        vault.set_limits(window_limit, window_duration, withdrawal_delay)?;
+        vault.window_start = Clock::get()?.unix_timestamp;
+        vault.window_withdrawn = 0;
+        vault.withdrawal_count = 0;
+        vault.open_requests = 0;
+        vault.open_allowances = 0;
+        vault.open_mints = 0;
         msg!("Vault initialized with authority: {}", vault.authority);
         Ok(())
     }
 
+    pub fn update_limits(
+        ctx: Context<UpdateLimits>,
+        window_limit: u64,
+        window_duration: i64,
+        withdrawal_delay: i64,
+    ) -> Result<()> {
+        // A stolen authority key alone cannot raise the cap; the guardian must co-sign.
+        ctx.accounts
+            .vault
+            .set_limits(window_limit, window_duration, withdrawal_delay)?;
+        msg!("Vault limit set to {} lamports per {} seconds", window_limit, window_duration);
+        Ok(())
+    }
+
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? += amount;
-        **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? -= amount;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        // The depositor is owned by the System Program, so lamports move through a CPI.
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
         msg!("Deposited {} lamports into vault", amount);
         Ok(())
     }
@@ -26,11 +68,342 @@ pub mod pda_vault {
             ctx.accounts.authority.key() == ctx.accounts.vault.authority,
             ErrorCode::Unauthorized
         );
-        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? += amount;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.vault.consume_window(amount, now)?;
+        debit_vault(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            amount,
+        )?;
         msg!("Withdrew {} lamports from vault", amount);
         Ok(())
     }
+
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, amount: u64) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        let pending = &mut ctx.accounts.pending_withdrawal;
+        pending.open(
+            &mut ctx.accounts.vault,
+            vault_key,
+            None,
+            amount,
+            ctx.bumps.pending_withdrawal,
+        )?;
+        msg!("Withdrawal of {} lamports unlocks at {}", amount, pending.unlock_at);
+        Ok(())
+    }
+
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.pending_withdrawal.amount;
+        require!(
+            now >= ctx.accounts.pending_withdrawal.unlock_at,
+            ErrorCode::WithdrawalLocked
+        );
+        debit_vault(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            amount,
+        )?;
+        let vault = &mut ctx.accounts.vault;
+        vault.open_requests = vault
+            .open_requests
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!("Executed delayed withdrawal of {} lamports", amount);
+        Ok(())
+    }
+
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.open_requests = vault
+            .open_requests
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!(
+            "Guardian cancelled withdrawal of {} lamports",
+            ctx.accounts.pending_withdrawal.amount
+        );
+        Ok(())
+    }
+
+    pub fn grant_allowance(
+        ctx: Context<GrantAllowance>,
+        amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            ErrorCode::InvalidExpiry
+        );
+        let allowance = &mut ctx.accounts.allowance;
+        allowance.vault = ctx.accounts.vault.key();
+        allowance.delegate = ctx.accounts.delegate.key();
+        allowance.remaining = amount;
+        allowance.expires_at = expires_at;
+        allowance.bump = ctx.bumps.allowance;
+        let vault = &mut ctx.accounts.vault;
+        vault.open_allowances = vault
+            .open_allowances
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!("Granted {} lamports to {}", amount, allowance.delegate);
+        Ok(())
+    }
+
+    pub fn revoke_allowance(ctx: Context<RevokeAllowance>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.open_allowances = vault
+            .open_allowances
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!("Revoked allowance for {}", ctx.accounts.allowance.delegate);
+        Ok(())
+    }
+
+    pub fn withdraw_as_delegate(ctx: Context<WithdrawAsDelegate>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let allowance = &mut ctx.accounts.allowance;
+        require!(now < allowance.expires_at, ErrorCode::AllowanceExpired);
+        allowance.remaining = allowance
+            .remaining
+            .checked_sub(amount)
+            .ok_or(ErrorCode::AllowanceExceeded)?;
+        // Delegated spending counts against the same window cap as the authority's.
+        ctx.accounts.vault.consume_window(amount, now)?;
+
+        debit_vault(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.delegate.to_account_info(),
+            amount,
+        )?;
+        emit!(DelegateWithdrawal {
+            vault: ctx.accounts.vault.key(),
+            delegate: ctx.accounts.delegate.key(),
+            amount,
+            remaining: ctx.accounts.allowance.remaining,
+        });
+        Ok(())
+    }
+
+    pub fn add_mint(ctx: Context<AddMint>, window_limit: u64) -> Result<()> {
+        let balance = &mut ctx.accounts.mint_balance;
+        balance.vault = ctx.accounts.vault.key();
+        balance.mint = ctx.accounts.mint.key();
+        balance.total_deposited = 0;
+        balance.total_withdrawn = 0;
+        balance.bump = ctx.bumps.mint_balance;
+        // Token amounts are in the mint's base units, so each mint gets its own cap; the
+        // window length and time-lock delay are shared with lamports.
+        balance.window_limit = window_limit;
+        balance.window_start = Clock::get()?.unix_timestamp;
+        balance.window_withdrawn = 0;
+        let vault = &mut ctx.accounts.vault;
+        vault.open_mints = vault
+            .open_mints
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!("Vault now accepts mint {}", balance.mint);
+        Ok(())
+    }
+
+    pub fn remove_mint(ctx: Context<RemoveMint>) -> Result<()> {
+        require!(
+            ctx.accounts.vault_token_account.amount == 0,
+            ErrorCode::MintNotEmpty
+        );
+        let vault = &ctx.accounts.vault;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", vault.authority.as_ref(), &[vault.bump]]];
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.vault_token_account.to_account_info(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        let vault = &mut ctx.accounts.vault;
+        vault.open_mints = vault
+            .open_mints
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!("Vault no longer accepts mint {}", ctx.accounts.mint.key());
+        Ok(())
+    }
+
+    pub fn update_mint_limit(ctx: Context<UpdateMintLimit>, window_limit: u64) -> Result<()> {
+        let balance = &mut ctx.accounts.mint_balance;
+        balance.window_limit = window_limit;
+        msg!("Mint {} limit set to {} per window", balance.mint, window_limit);
+        Ok(())
+    }
+
+    pub fn deposit_token(ctx: Context<DepositToken>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let cpi_accounts = token::Transfer {
+            from: ctx.accounts.depositor_token_account.to_account_info(),
+            to: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.depositor.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )?;
+
+        let balance = &mut ctx.accounts.mint_balance;
+        balance.total_deposited = balance
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!("Deposited {} tokens of mint {}", amount, balance.mint);
+        Ok(())
+    }
+
+    pub fn withdraw_token(ctx: Context<WithdrawToken>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let window_duration = ctx.accounts.vault.window_duration;
+        ctx.accounts
+            .mint_balance
+            .consume_window(amount, window_duration, now)?;
+        send_tokens(
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.destination,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        ctx.accounts.mint_balance.record_withdrawal(amount)?;
+        msg!("Withdrew {} tokens of mint {}", amount, ctx.accounts.mint.key());
+        Ok(())
+    }
+
+    pub fn request_token_withdrawal(
+        ctx: Context<RequestTokenWithdrawal>,
+        amount: u64,
+    ) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        let pending = &mut ctx.accounts.pending_withdrawal;
+        pending.open(
+            &mut ctx.accounts.vault,
+            vault_key,
+            Some(ctx.accounts.mint.key()),
+            amount,
+            ctx.bumps.pending_withdrawal,
+        )?;
+        msg!("Withdrawal of {} tokens unlocks at {}", amount, pending.unlock_at);
+        Ok(())
+    }
+
+    pub fn execute_token_withdrawal(ctx: Context<ExecuteTokenWithdrawal>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.pending_withdrawal.amount;
+        require!(
+            now >= ctx.accounts.pending_withdrawal.unlock_at,
+            ErrorCode::WithdrawalLocked
+        );
+        send_tokens(
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.destination,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        ctx.accounts.mint_balance.record_withdrawal(amount)?;
+        let vault = &mut ctx.accounts.vault;
+        vault.open_requests = vault
+            .open_requests
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        msg!("Executed delayed withdrawal of {} tokens", amount);
+        Ok(())
+    }
+
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        // The guardian co-signs, so a stolen authority key cannot close the vault and re-create
+        // it under a guardian of the thief's choosing.
+        let vault = &ctx.accounts.vault;
+        // Children that point at the vault would come back to life if it were re-created.
+        require!(
+            vault.open_requests == 0 && vault.open_allowances == 0 && vault.open_mints == 0,
+            ErrorCode::VaultInUse
+        );
+        // Only the rent comes back here; everything above it leaves through the capped or
+        // delayed withdrawal paths first.
+        let info = vault.to_account_info();
+        let floor = Rent::get()?.minimum_balance(info.data_len());
+        require!(info.lamports() <= floor, ErrorCode::VaultNotEmpty);
+        msg!("Closing vault, returning {} lamports of rent", info.lamports());
+        Ok(())
+    }
+}
+
+// Moves lamports out of the vault without dropping it below the rent-exempt minimum.
+fn debit_vault(vault: &AccountInfo, recipient: &AccountInfo, amount: u64) -> Result<()> {
+    let floor = Rent::get()?.minimum_balance(vault.data_len());
+    let remaining = vault
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::InsufficientFunds)?;
+    require!(remaining >= floor, ErrorCode::BelowRentExempt);
+    let credited = recipient
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    **vault.try_borrow_mut_lamports()? = remaining;
+    **recipient.try_borrow_mut_lamports()? = credited;
+    Ok(())
+}
+
+// The vault PDA owns every token account, so it signs with its own seeds.
+fn send_tokens<'info>(
+    vault: &Account<'info, Vault>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"vault", vault.authority.as_ref(), &[vault.bump]]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+// Windows are fixed-length; the first withdrawal after one ends starts the next. Shared by
+// the lamport window on Vault and the per-mint windows on MintBalance.
+fn consume_window(
+    window_start: &mut i64,
+    window_withdrawn: &mut u64,
+    window_limit: u64,
+    window_duration: i64,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    let window_end = window_start
+        .checked_add(window_duration)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if now >= window_end {
+        *window_start = now;
+        *window_withdrawn = 0;
+    }
+    let total = window_withdrawn
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(total <= window_limit, ErrorCode::WithdrawalLimitExceeded);
+    *window_withdrawn = total;
+    Ok(())
 }
 
 #[derive(Accounts)]
@@ -45,12 +418,31 @@ pub struct Initialize<'info> {
     pub vault: Account<'info, Vault>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub guardian: SystemAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        has_one = guardian @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    pub guardian: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct Deposit<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump
+    )]
     pub vault: Account<'info, Vault>,
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -69,19 +461,537 @@ pub struct Withdraw<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingWithdrawal::LEN,
+        seeds = [b"withdrawal", vault.key().as_ref(), &vault.withdrawal_count.to_le_bytes()],
+        bump
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"withdrawal", vault.key().as_ref(), &pending_withdrawal.index.to_le_bytes()],
+        bump = pending_withdrawal.bump,
+        has_one = vault @ ErrorCode::InvalidWithdrawal,
+        constraint = pending_withdrawal.mint.is_none() @ ErrorCode::InvalidWithdrawal,
+        close = authority
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        has_one = guardian @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"withdrawal", vault.key().as_ref(), &pending_withdrawal.index.to_le_bytes()],
+        bump = pending_withdrawal.bump,
+        has_one = vault @ ErrorCode::InvalidWithdrawal,
+        close = authority
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    /// CHECK: receives the request's rent; must match the vault authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GrantAllowance<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Allowance::LEN,
+        seeds = [b"allowance", vault.key().as_ref(), delegate.key().as_ref()],
+        bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+    pub delegate: SystemAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAllowance<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"allowance", vault.key().as_ref(), allowance.delegate.as_ref()],
+        bump = allowance.bump,
+        has_one = vault @ ErrorCode::InvalidAllowance,
+        close = authority
+    )]
+    pub allowance: Account<'info, Allowance>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAsDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"allowance", vault.key().as_ref(), delegate.key().as_ref()],
+        bump = allowance.bump,
+        has_one = vault @ ErrorCode::InvalidAllowance,
+        has_one = delegate @ ErrorCode::Unauthorized
+    )]
+    pub allowance: Account<'info, Allowance>,
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        has_one = guardian @ ErrorCode::Unauthorized,
+        close = authority
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MintBalance::LEN,
+        seeds = [b"mint_balance", vault.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub mint_balance: Account<'info, MintBalance>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"mint_balance", vault.key().as_ref(), mint.key().as_ref()],
+        bump = mint_balance.bump,
+        close = authority
+    )]
+    pub mint_balance: Account<'info, MintBalance>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToken<'info> {
+    #[account(seeds = [b"vault", vault.authority.as_ref()], bump = vault.bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"mint_balance", vault.key().as_ref(), mint.key().as_ref()],
+        bump = mint_balance.bump
+    )]
+    pub mint_balance: Account<'info, MintBalance>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::authority = depositor)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+    pub depositor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawToken<'info> {
+    #[account(
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"mint_balance", vault.key().as_ref(), mint.key().as_ref()],
+        bump = mint_balance.bump
+    )]
+    pub mint_balance: Account<'info, MintBalance>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint)]
+    pub destination: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMintLimit<'info> {
+    #[account(
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        has_one = guardian @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"mint_balance", vault.key().as_ref(), mint_balance.mint.as_ref()],
+        bump = mint_balance.bump
+    )]
+    pub mint_balance: Account<'info, MintBalance>,
+    pub authority: Signer<'info>,
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequestTokenWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    // Requests are only accepted for mints the vault tracks.
+    #[account(
+        seeds = [b"mint_balance", vault.key().as_ref(), mint.key().as_ref()],
+        bump = mint_balance.bump
+    )]
+    pub mint_balance: Account<'info, MintBalance>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingWithdrawal::LEN,
+        seeds = [b"withdrawal", vault.key().as_ref(), &vault.withdrawal_count.to_le_bytes()],
+        bump
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTokenWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"withdrawal", vault.key().as_ref(), &pending_withdrawal.index.to_le_bytes()],
+        bump = pending_withdrawal.bump,
+        has_one = vault @ ErrorCode::InvalidWithdrawal,
+        constraint = pending_withdrawal.mint == Some(mint.key()) @ ErrorCode::InvalidWithdrawal,
+        close = authority
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    #[account(
+        mut,
+        seeds = [b"mint_balance", vault.key().as_ref(), mint.key().as_ref()],
+        bump = mint_balance.bump
+    )]
+    pub mint_balance: Account<'info, MintBalance>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint)]
+    pub destination: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[account]
 pub struct Vault {
     pub authority: Pubkey,
     pub bump: u8,
+    pub guardian: Pubkey,
+    pub window_limit: u64,
+    pub window_duration: i64,
+    pub window_start: i64,
+    pub window_withdrawn: u64,
+    pub withdrawal_delay: i64,
+    pub withdrawal_count: u64,
+    pub open_requests: u32,
+    pub open_allowances: u32,
+    pub open_mints: u32,
 }
 
 impl Vault {
-    pub const LEN: usize = 32 + 1;
+    pub const LEN: usize = 32 + 1 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 4 + 4 + 4;
+
+    pub fn set_limits(
+        &mut self,
+        window_limit: u64,
+        window_duration: i64,
+        withdrawal_delay: i64,
+    ) -> Result<()> {
+        require!(window_duration > 0, ErrorCode::InvalidWindow);
+        require!(withdrawal_delay > 0, ErrorCode::InvalidDelay);
+        self.window_limit = window_limit;
+        self.window_duration = window_duration;
+        self.withdrawal_delay = withdrawal_delay;
+        Ok(())
+    }
+
+    pub fn consume_window(&mut self, amount: u64, now: i64) -> Result<()> {
+        consume_window(
+            &mut self.window_start,
+            &mut self.window_withdrawn,
+            self.window_limit,
+            self.window_duration,
+            amount,
+            now,
+        )
+    }
+}
+
+// Withdrawals above the window cap wait here until unlock_at; the guardian can cancel.
+// `mint` is None for lamports and the token's mint otherwise.
+#[account]
+pub struct PendingWithdrawal {
+    pub vault: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub unlock_at: i64,
+    pub bump: u8,
+    pub mint: Option<Pubkey>,
+}
+
+impl PendingWithdrawal {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 1 + (1 + 32);
+
+    // Shared by lamport and token requests; takes the next index from the vault counter.
+    pub fn open(
+        &mut self,
+        vault: &mut Vault,
+        vault_key: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+        bump: u8,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        self.vault = vault_key;
+        self.index = vault.withdrawal_count;
+        self.amount = amount;
+        self.unlock_at = now
+            .checked_add(vault.withdrawal_delay)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.bump = bump;
+        self.mint = mint;
+        vault.withdrawal_count = vault
+            .withdrawal_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        vault.open_requests = vault
+            .open_requests
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
+
+// Lets a delegate withdraw up to `remaining` lamports until `expires_at`.
+#[account]
+pub struct Allowance {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub remaining: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Allowance {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1;
+}
+
+#[event]
+pub struct DelegateWithdrawal {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+// One child PDA per mint keeps running totals and that mint's withdrawal window next to
+// the vault's token account.
+#[account]
+pub struct MintBalance {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub bump: u8,
+    pub window_limit: u64,
+    pub window_start: i64,
+    pub window_withdrawn: u64,
+}
+
+impl MintBalance {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 8 + 8 + 8;
+
+    pub fn consume_window(&mut self, amount: u64, window_duration: i64, now: i64) -> Result<()> {
+        consume_window(
+            &mut self.window_start,
+            &mut self.window_withdrawn,
+            self.window_limit,
+            window_duration,
+            amount,
+            now,
+        )
+    }
+
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.total_withdrawn = self
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("You are not authorized to perform this action")]
     Unauthorized,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Guardian must differ from the authority")]
+    InvalidGuardian,
+    #[msg("Window duration must be positive")]
+    InvalidWindow,
+    #[msg("Withdrawal delay must be positive")]
+    InvalidDelay,
+    #[msg("Withdrawal exceeds the window limit; use request_withdrawal")]
+    WithdrawalLimitExceeded,
+    #[msg("Withdrawal is still time-locked")]
+    WithdrawalLocked,
+    #[msg("Withdrawal request does not match this vault or asset")]
+    InvalidWithdrawal,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Allowance does not belong to this vault")]
+    InvalidAllowance,
+    #[msg("Allowance has expired")]
+    AllowanceExpired,
+    #[msg("Amount exceeds the remaining allowance")]
+    AllowanceExceeded,
+    #[msg("Vault does not hold enough lamports")]
+    InsufficientFunds,
+    #[msg("Withdrawal would leave the vault below its rent-exempt minimum")]
+    BelowRentExempt,
+    #[msg("Cancel pending withdrawals, revoke allowances and remove mints before closing")]
+    VaultInUse,
+    #[msg("Withdraw every token before removing the mint")]
+    MintNotEmpty,
+    #[msg("Withdraw everything above the rent-exempt minimum before closing")]
+    VaultNotEmpty,
 }
 
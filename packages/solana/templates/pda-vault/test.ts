@@ -1,13 +1,31 @@
 const INSTRUCTIONS = [
   {
     name: "initialize",
-    args: [],
+    args: [
+      { name: "window_limit", type: "u64" },
+      { name: "window_duration", type: "i64" },
+      { name: "withdrawal_delay", type: "i64" },
+    ],
     accounts: [
       { name: "vault", isMut: true, isSigner: false },
       { name: "authority", isMut: true, isSigner: true },
+      { name: "guardian", isMut: false, isSigner: false },
       { name: "system_program", isMut: false, isSigner: false },
     ],
   },
+  {
+    name: "update_limits",
+    args: [
+      { name: "window_limit", type: "u64" },
+      { name: "window_duration", type: "i64" },
+      { name: "withdrawal_delay", type: "i64" },
+    ],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "authority", isMut: false, isSigner: true },
+      { name: "guardian", isMut: false, isSigner: true },
+    ],
+  },
   {
     name: "deposit",
     args: [{ name: "amount", type: "u64" }],
@@ -25,6 +43,159 @@ const INSTRUCTIONS = [
       { name: "authority", isMut: true, isSigner: true },
     ],
   },
+  {
+    name: "request_withdrawal",
+    args: [{ name: "amount", type: "u64" }],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "pending_withdrawal", isMut: true, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+    ],
+  },
+  {
+    name: "execute_withdrawal",
+    args: [],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "pending_withdrawal", isMut: true, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+    ],
+  },
+  {
+    name: "cancel_withdrawal",
+    args: [],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "pending_withdrawal", isMut: true, isSigner: false },
+      { name: "authority", isMut: true, isSigner: false },
+      { name: "guardian", isMut: false, isSigner: true },
+    ],
+  },
+  {
+    name: "grant_allowance",
+    args: [
+      { name: "amount", type: "u64" },
+      { name: "expires_at", type: "i64" },
+    ],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "allowance", isMut: true, isSigner: false },
+      { name: "delegate", isMut: false, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+    ],
+  },
+  {
+    name: "revoke_allowance",
+    args: [],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "allowance", isMut: true, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+    ],
+  },
+  {
+    name: "withdraw_as_delegate",
+    args: [{ name: "amount", type: "u64" }],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "allowance", isMut: true, isSigner: false },
+      { name: "delegate", isMut: true, isSigner: true },
+    ],
+  },
+  {
+    name: "add_mint",
+    args: [{ name: "window_limit", type: "u64" }],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "mint_balance", isMut: true, isSigner: false },
+      { name: "vault_token_account", isMut: true, isSigner: false },
+      { name: "mint", isMut: false, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+      { name: "associated_token_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "remove_mint",
+    args: [],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "mint_balance", isMut: true, isSigner: false },
+      { name: "vault_token_account", isMut: true, isSigner: false },
+      { name: "mint", isMut: false, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "update_mint_limit",
+    args: [{ name: "window_limit", type: "u64" }],
+    accounts: [
+      { name: "vault", isMut: false, isSigner: false },
+      { name: "mint_balance", isMut: true, isSigner: false },
+      { name: "authority", isMut: false, isSigner: true },
+      { name: "guardian", isMut: false, isSigner: true },
+    ],
+  },
+  {
+    name: "deposit_token",
+    args: [{ name: "amount", type: "u64" }],
+    accounts: [
+      { name: "vault", isMut: false, isSigner: false },
+      { name: "mint_balance", isMut: true, isSigner: false },
+      { name: "vault_token_account", isMut: true, isSigner: false },
+      { name: "depositor_token_account", isMut: true, isSigner: false },
+      { name: "depositor", isMut: false, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "withdraw_token",
+    args: [{ name: "amount", type: "u64" }],
+    accounts: [
+      { name: "vault", isMut: false, isSigner: false },
+      { name: "mint_balance", isMut: true, isSigner: false },
+      { name: "vault_token_account", isMut: true, isSigner: false },
+      { name: "destination", isMut: true, isSigner: false },
+      { name: "authority", isMut: false, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "request_token_withdrawal",
+    args: [{ name: "amount", type: "u64" }],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "mint_balance", isMut: false, isSigner: false },
+      { name: "pending_withdrawal", isMut: true, isSigner: false },
+      { name: "mint", isMut: false, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+      { name: "system_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "execute_token_withdrawal",
+    args: [],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "pending_withdrawal", isMut: true, isSigner: false },
+      { name: "mint_balance", isMut: true, isSigner: false },
+      { name: "vault_token_account", isMut: true, isSigner: false },
+      { name: "destination", isMut: true, isSigner: false },
+      { name: "mint", isMut: false, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+      { name: "token_program", isMut: false, isSigner: false },
+    ],
+  },
+  {
+    name: "close_vault",
+    args: [],
+    accounts: [
+      { name: "vault", isMut: true, isSigner: false },
+      { name: "authority", isMut: true, isSigner: true },
+      { name: "guardian", isMut: false, isSigner: true },
+    ],
+  },
 ];
 
 function norm(name) {
@@ -103,7 +274,7 @@ describe("pda-vault behavior", () => {
   it("exposes builder APIs for initialize/deposit/withdraw", () => {
     const methods = program.methods || {};
 
-    const initializeBuilder = methods[snakeToCamel("initialize")]();
+    const initializeBuilder = methods[snakeToCamel("initialize")](1, 86400, 3600);
     expect(initializeBuilder && initializeBuilder.accounts).to.be.a("function");
     expect(initializeBuilder && initializeBuilder.rpc).to.be.a("function");
 
@@ -116,6 +287,67 @@ describe("pda-vault behavior", () => {
     expect(withdrawBuilder && withdrawBuilder.rpc).to.be.a("function");
   });
 
+  it("exposes builder APIs for time-locked withdrawals", () => {
+    const methods = program.methods || {};
+
+    const updateLimitsBuilder = methods[snakeToCamel("update_limits")](1, 86400, 3600);
+    expect(updateLimitsBuilder && updateLimitsBuilder.rpc).to.be.a("function");
+
+    const requestBuilder = methods[snakeToCamel("request_withdrawal")](1);
+    expect(requestBuilder && requestBuilder.rpc).to.be.a("function");
+
+    const executeBuilder = methods[snakeToCamel("execute_withdrawal")]();
+    expect(executeBuilder && executeBuilder.rpc).to.be.a("function");
+
+    const cancelBuilder = methods[snakeToCamel("cancel_withdrawal")]();
+    expect(cancelBuilder && cancelBuilder.rpc).to.be.a("function");
+  });
+
+  it("exposes builder APIs for delegated allowances", () => {
+    const methods = program.methods || {};
+
+    const grantBuilder = methods[snakeToCamel("grant_allowance")](1, 1);
+    expect(grantBuilder && grantBuilder.rpc).to.be.a("function");
+
+    const revokeBuilder = methods[snakeToCamel("revoke_allowance")]();
+    expect(revokeBuilder && revokeBuilder.rpc).to.be.a("function");
+
+    const delegateBuilder = methods[snakeToCamel("withdraw_as_delegate")](1);
+    expect(delegateBuilder && delegateBuilder.rpc).to.be.a("function");
+  });
+
+  it("exposes a builder API for closing the vault", () => {
+    const methods = program.methods || {};
+
+    const closeBuilder = methods[snakeToCamel("close_vault")]();
+    expect(closeBuilder && closeBuilder.rpc).to.be.a("function");
+
+    const removeMintBuilder = methods[snakeToCamel("remove_mint")]();
+    expect(removeMintBuilder && removeMintBuilder.rpc).to.be.a("function");
+  });
+
+  it("exposes builder APIs for token deposits and withdrawals", () => {
+    const methods = program.methods || {};
+
+    const addMintBuilder = methods[snakeToCamel("add_mint")](1);
+    expect(addMintBuilder && addMintBuilder.rpc).to.be.a("function");
+
+    const updateMintLimitBuilder = methods[snakeToCamel("update_mint_limit")](1);
+    expect(updateMintLimitBuilder && updateMintLimitBuilder.rpc).to.be.a("function");
+
+    const depositTokenBuilder = methods[snakeToCamel("deposit_token")](1);
+    expect(depositTokenBuilder && depositTokenBuilder.rpc).to.be.a("function");
+
+    const withdrawTokenBuilder = methods[snakeToCamel("withdraw_token")](1);
+    expect(withdrawTokenBuilder && withdrawTokenBuilder.rpc).to.be.a("function");
+
+    const requestTokenBuilder = methods[snakeToCamel("request_token_withdrawal")](1);
+    expect(requestTokenBuilder && requestTokenBuilder.rpc).to.be.a("function");
+
+    const executeTokenBuilder = methods[snakeToCamel("execute_token_withdrawal")]();
+    expect(executeTokenBuilder && executeTokenBuilder.rpc).to.be.a("function");
+  });
+
   it("exposes Vault state with authority and bump fields", () => {
     const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
     const vault = accounts.find(function (entry) {
@@ -135,4 +367,153 @@ describe("pda-vault behavior", () => {
     ).to.equal(true);
     expect(fieldMap[norm("bump")]).to.equal("u8");
   });
+
+  it("stores the withdrawal window, guardian and time-lock delay on Vault", () => {
+    const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
+    const vault = accounts.find(function (entry) {
+      return norm(entry.name) === norm("Vault");
+    });
+    const fields = Array.isArray(vault && vault.type && vault.type.fields) ? vault.type.fields : [];
+    const fieldMap = Object.fromEntries(
+      fields.map(function (field) {
+        return [norm(field.name), typeToString(field.type)];
+      })
+    );
+
+    expect(
+      fieldMap[norm("guardian")] === "pubkey" || fieldMap[norm("guardian")] === "publicKey"
+    ).to.equal(true);
+    expect(fieldMap[norm("window_limit")]).to.equal("u64");
+    expect(fieldMap[norm("window_duration")]).to.equal("i64");
+    expect(fieldMap[norm("window_start")]).to.equal("i64");
+    expect(fieldMap[norm("window_withdrawn")]).to.equal("u64");
+    expect(fieldMap[norm("withdrawal_delay")]).to.equal("i64");
+    expect(fieldMap[norm("withdrawal_count")]).to.equal("u64");
+
+    const pending = accounts.find(function (entry) {
+      return norm(entry.name) === norm("PendingWithdrawal");
+    });
+    expect(pending, "PendingWithdrawal state definition should exist in IDL").to.exist;
+    const pendingFields = (pending.type && pending.type.fields) || [];
+    expect(pendingFields.map(function (field) { return norm(field.name); })).to.include.members(
+      ["vault", "index", "amount", "unlockat", "bump"]
+    );
+
+    const errors = (program.idl.errors || []).map(function (entry) {
+      return norm(entry.name);
+    });
+    expect(errors).to.include(norm("WithdrawalLimitExceeded"));
+    expect(errors).to.include(norm("WithdrawalLocked"));
+  });
+
+  it("stores Allowance PDAs and emits DelegateWithdrawal events", () => {
+    const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
+    const allowance = accounts.find(function (entry) {
+      return norm(entry.name) === norm("Allowance");
+    });
+
+    expect(allowance, "Allowance state definition should exist in IDL").to.exist;
+    const fields = Array.isArray(allowance.type && allowance.type.fields) ? allowance.type.fields : [];
+    const fieldMap = Object.fromEntries(
+      fields.map(function (field) {
+        return [norm(field.name), typeToString(field.type)];
+      })
+    );
+    expect(fieldMap[norm("remaining")]).to.equal("u64");
+    expect(fieldMap[norm("expires_at")]).to.equal("i64");
+
+    const events = Array.isArray(program.idl && program.idl.events) ? program.idl.events : [];
+    const event = events.find(function (entry) {
+      return norm(entry.name) === norm("DelegateWithdrawal");
+    });
+    expect(event, "DelegateWithdrawal event should exist in IDL").to.exist;
+    const types = Array.isArray(program.idl && program.idl.types) ? program.idl.types : [];
+    const eventType = types.find(function (entry) {
+      return norm(entry.name) === norm("DelegateWithdrawal");
+    });
+    const eventFields = (event.fields || (eventType && eventType.type && eventType.type.fields) || []).map(function (field) {
+      return norm(field.name);
+    });
+    expect(eventFields).to.include.members(["vault", "delegate", "amount", "remaining"]);
+
+    const errors = (program.idl.errors || []).map(function (entry) {
+      return norm(entry.name);
+    });
+    expect(errors).to.include(norm("AllowanceExpired"));
+    expect(errors).to.include(norm("AllowanceExceeded"));
+  });
+
+  it("tracks per-mint totals in MintBalance child accounts", () => {
+    const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
+    const balance = accounts.find(function (entry) {
+      return norm(entry.name) === norm("MintBalance");
+    });
+
+    expect(balance, "MintBalance state definition should exist in IDL").to.exist;
+    const fields = Array.isArray(balance.type && balance.type.fields) ? balance.type.fields : [];
+    const fieldMap = Object.fromEntries(
+      fields.map(function (field) {
+        return [norm(field.name), typeToString(field.type)];
+      })
+    );
+
+    ["vault", "mint"].forEach(function (name) {
+      expect(fieldMap[name] === "pubkey" || fieldMap[name] === "publicKey", name + " should be a pubkey").to.equal(true);
+    });
+    expect(fieldMap[norm("total_deposited")]).to.equal("u64");
+    expect(fieldMap[norm("total_withdrawn")]).to.equal("u64");
+    expect(fieldMap[norm("bump")]).to.equal("u8");
+  });
+
+  it("caps and time-locks token withdrawals per mint", () => {
+    const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
+    const balance = accounts.find(function (entry) {
+      return norm(entry.name) === norm("MintBalance");
+    });
+    const fields = Array.isArray(balance && balance.type && balance.type.fields) ? balance.type.fields : [];
+    const fieldMap = Object.fromEntries(
+      fields.map(function (field) {
+        return [norm(field.name), typeToString(field.type)];
+      })
+    );
+    expect(fieldMap[norm("window_limit")]).to.equal("u64");
+    expect(fieldMap[norm("window_start")]).to.equal("i64");
+    expect(fieldMap[norm("window_withdrawn")]).to.equal("u64");
+
+    const pending = accounts.find(function (entry) {
+      return norm(entry.name) === norm("PendingWithdrawal");
+    });
+    const pendingFields = (pending && pending.type && pending.type.fields) || [];
+    const mint = pendingFields.find(function (field) {
+      return norm(field.name) === norm("mint");
+    });
+    expect(mint, "PendingWithdrawal should record the mint of a token request").to.exist;
+    expect(typeToString(mint.type)).to.include("option");
+  });
+
+  it("counts open requests, allowances and mints and guards the rent-exempt floor", () => {
+    const accounts = Array.isArray(program.idl && program.idl.accounts) ? program.idl.accounts : [];
+    const vault = accounts.find(function (entry) {
+      return norm(entry.name) === norm("Vault");
+    });
+    const fields = Array.isArray(vault && vault.type && vault.type.fields) ? vault.type.fields : [];
+    const fieldMap = Object.fromEntries(
+      fields.map(function (field) {
+        return [norm(field.name), typeToString(field.type)];
+      })
+    );
+
+    expect(fieldMap[norm("open_requests")]).to.equal("u32");
+    expect(fieldMap[norm("open_allowances")]).to.equal("u32");
+    expect(fieldMap[norm("open_mints")]).to.equal("u32");
+
+    const errors = (program.idl.errors || []).map(function (entry) {
+      return norm(entry.name);
+    });
+    expect(errors).to.include(norm("InsufficientFunds"));
+    expect(errors).to.include(norm("BelowRentExempt"));
+    expect(errors).to.include(norm("VaultInUse"));
+    expect(errors).to.include(norm("MintNotEmpty"));
+    expect(errors).to.include(norm("VaultNotEmpty"));
+  });
 });
@@ -0,0 +1,345 @@
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import {
+  createMint,
+  exists,
+  expectError,
+  lamports,
+  mintTo,
+  newWallet,
+  now,
+  startTemplate,
+  tokenBalance,
+  warp,
+} from "../../../template-tests/bankrun";
+
+const WINDOW_DURATION = 86_400;
+const WITHDRAWAL_DELAY = 3_600;
+const TOKEN_WINDOW_LIMIT = 100;
+
+interface VaultFixture {
+  authority: Keypair;
+  guardian: Keypair;
+  vault: PublicKey;
+  mint: PublicKey;
+  mintBalance: PublicKey;
+  vaultTokens: PublicKey;
+  // The authority's own token account, used as the withdrawal destination.
+  destination: PublicKey;
+}
+
+let program: Program;
+
+const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+const vaultPda = (authority: PublicKey) => pda(Buffer.from("vault"), authority.toBuffer());
+const mintBalancePda = (vault: PublicKey, mint: PublicKey) => pda(Buffer.from("mint_balance"), vault.toBuffer(), mint.toBuffer());
+const withdrawalPda = (vault: PublicKey, index: BN) => pda(Buffer.from("withdrawal"), vault.toBuffer(), index.toArrayLike(Buffer, "le", 8));
+const allowancePda = (vault: PublicKey, delegate: PublicKey) => pda(Buffer.from("allowance"), vault.toBuffer(), delegate.toBuffer());
+
+// A vault that tracks one mint, capped at TOKEN_WINDOW_LIMIT per window, holding `deposited` tokens.
+async function createVault(deposited = 1_000): Promise<VaultFixture> {
+  const authority = newWallet();
+  const guardian = Keypair.generate();
+  const vault = vaultPda(authority.publicKey);
+  await program.methods
+    .initialize(new BN(1_000_000_000), new BN(WINDOW_DURATION), new BN(WITHDRAWAL_DELAY))
+    .accountsStrict({
+      vault,
+      authority: authority.publicKey,
+      guardian: guardian.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+
+  const mint = await createMint();
+  const f: VaultFixture = {
+    authority,
+    guardian,
+    vault,
+    mint,
+    mintBalance: mintBalancePda(vault, mint),
+    vaultTokens: getAssociatedTokenAddressSync(mint, vault, true),
+    destination: await mintTo(mint, authority.publicKey, deposited),
+  };
+  await program.methods
+    .addMint(new BN(TOKEN_WINDOW_LIMIT))
+    .accountsStrict({
+      vault,
+      mintBalance: f.mintBalance,
+      vaultTokenAccount: f.vaultTokens,
+      mint,
+      authority: authority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+  await program.methods
+    .depositToken(new BN(deposited))
+    .accountsStrict({
+      vault,
+      mintBalance: f.mintBalance,
+      vaultTokenAccount: f.vaultTokens,
+      depositorTokenAccount: f.destination,
+      mint,
+      depositor: authority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([authority])
+    .rpc();
+  return f;
+}
+
+function withdrawToken(f: VaultFixture, amount: number) {
+  return program.methods
+    .withdrawToken(new BN(amount))
+    .accountsStrict({
+      vault: f.vault,
+      mintBalance: f.mintBalance,
+      vaultTokenAccount: f.vaultTokens,
+      destination: f.destination,
+      mint: f.mint,
+      authority: f.authority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([f.authority])
+    .rpc();
+}
+
+async function requestTokenWithdrawal(f: VaultFixture, amount: number): Promise<PublicKey> {
+  const { withdrawalCount } = await program.account.vault.fetch(f.vault);
+  const pendingWithdrawal = withdrawalPda(f.vault, withdrawalCount);
+  await program.methods
+    .requestTokenWithdrawal(new BN(amount))
+    .accountsStrict({
+      vault: f.vault,
+      mintBalance: f.mintBalance,
+      pendingWithdrawal,
+      mint: f.mint,
+      authority: f.authority.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([f.authority])
+    .rpc();
+  return pendingWithdrawal;
+}
+
+function executeTokenWithdrawal(f: VaultFixture, pendingWithdrawal: PublicKey) {
+  return program.methods
+    .executeTokenWithdrawal()
+    .accountsStrict({
+      vault: f.vault,
+      pendingWithdrawal,
+      mintBalance: f.mintBalance,
+      vaultTokenAccount: f.vaultTokens,
+      destination: f.destination,
+      mint: f.mint,
+      authority: f.authority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([f.authority])
+    .rpc();
+}
+
+function depositLamports(f: VaultFixture, amount: number) {
+  return program.methods
+    .deposit(new BN(amount))
+    .accountsStrict({ vault: f.vault, authority: f.authority.publicKey, systemProgram: SystemProgram.programId })
+    .signers([f.authority])
+    .rpc();
+}
+
+async function grantAllowance(f: VaultFixture, delegate: PublicKey, amount: number, expiresIn = 3_600): Promise<PublicKey> {
+  const allowance = allowancePda(f.vault, delegate);
+  await program.methods
+    .grantAllowance(new BN(amount), new BN(((await now()) + BigInt(expiresIn)).toString()))
+    .accountsStrict({
+      vault: f.vault,
+      allowance,
+      delegate,
+      authority: f.authority.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([f.authority])
+    .rpc();
+  return allowance;
+}
+
+function withdrawAsDelegate(f: VaultFixture, delegate: Keypair, amount: number) {
+  return program.methods
+    .withdrawAsDelegate(new BN(amount))
+    .accountsStrict({ vault: f.vault, allowance: allowancePda(f.vault, delegate.publicKey), delegate: delegate.publicKey })
+    .signers([delegate])
+    .rpc();
+}
+
+function revokeAllowance(f: VaultFixture, allowance: PublicKey) {
+  return program.methods
+    .revokeAllowance()
+    .accountsStrict({ vault: f.vault, allowance, authority: f.authority.publicKey })
+    .signers([f.authority])
+    .rpc();
+}
+
+function closeVault(f: VaultFixture, guardian = f.guardian) {
+  return program.methods
+    .closeVault()
+    .accountsStrict({ vault: f.vault, authority: f.authority.publicKey, guardian: guardian.publicKey })
+    .signers([f.authority, guardian])
+    .rpc();
+}
+
+function removeMint(f: VaultFixture) {
+  return program.methods
+    .removeMint()
+    .accountsStrict({
+      vault: f.vault,
+      mintBalance: f.mintBalance,
+      vaultTokenAccount: f.vaultTokens,
+      mint: f.mint,
+      authority: f.authority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([f.authority])
+    .rpc();
+}
+
+describe("pda-vault transactions", () => {
+  beforeEach(async () => {
+    program = await startTemplate("pda_vault");
+  });
+
+  it("caps direct token withdrawals at the mint's window limit", async () => {
+    const f = await createVault();
+    await withdrawToken(f, 60);
+    await expectError(withdrawToken(f, 41), "WithdrawalLimitExceeded");
+    await withdrawToken(f, 40);
+
+    // A new window resets what has been withdrawn.
+    await warp(WINDOW_DURATION);
+    await withdrawToken(f, TOKEN_WINDOW_LIMIT);
+    const balance = await program.account.mintBalance.fetch(f.mintBalance);
+    expect(balance.totalWithdrawn.toNumber()).to.equal(200);
+    expect(await tokenBalance(f.vaultTokens)).to.equal(800n);
+  });
+
+  it("time-locks token withdrawals above the cap", async () => {
+    const f = await createVault();
+    const pending = await requestTokenWithdrawal(f, 500);
+
+    await expectError(executeTokenWithdrawal(f, pending), "WithdrawalLocked");
+    await warp(WITHDRAWAL_DELAY);
+    await executeTokenWithdrawal(f, pending);
+
+    expect(await tokenBalance(f.vaultTokens)).to.equal(500n);
+    expect(await exists(pending)).to.equal(false);
+    const { openRequests } = await program.account.vault.fetch(f.vault);
+    expect(openRequests).to.equal(0);
+  });
+
+  it("keeps lamport and token requests apart", async () => {
+    const f = await createVault();
+    const tokenRequest = await requestTokenWithdrawal(f, 500);
+    const { withdrawalCount } = await program.account.vault.fetch(f.vault);
+    const lamportRequest = withdrawalPda(f.vault, withdrawalCount);
+    await program.methods
+      .requestWithdrawal(new BN(1_000))
+      .accountsStrict({
+        vault: f.vault,
+        pendingWithdrawal: lamportRequest,
+        authority: f.authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([f.authority])
+      .rpc();
+    await warp(WITHDRAWAL_DELAY);
+
+    // Neither request can be paid out as the other asset.
+    await expectError(executeTokenWithdrawal(f, lamportRequest), "InvalidWithdrawal");
+    await expectError(
+      program.methods
+        .executeWithdrawal()
+        .accountsStrict({ vault: f.vault, pendingWithdrawal: tokenRequest, authority: f.authority.publicKey })
+        .signers([f.authority])
+        .rpc(),
+      "InvalidWithdrawal"
+    );
+    expect(await tokenBalance(f.vaultTokens)).to.equal(1_000n);
+  });
+
+  it("lets a delegate spend its allowance until it runs out or expires", async () => {
+    const f = await createVault();
+    await depositLamports(f, 10_000_000);
+    const delegate = newWallet();
+    const allowance = await grantAllowance(f, delegate.publicKey, 1_000_000);
+    const before = await lamports(delegate.publicKey);
+
+    await withdrawAsDelegate(f, delegate, 600_000);
+    await expectError(withdrawAsDelegate(f, delegate, 400_001), "AllowanceExceeded");
+    await withdrawAsDelegate(f, delegate, 400_000);
+    expect(await lamports(delegate.publicKey)).to.equal(before + 1_000_000);
+    const { remaining } = await program.account.allowance.fetch(allowance);
+    expect(remaining.toNumber()).to.equal(0);
+
+    // A fresh grant is still cut off once it expires.
+    const other = newWallet();
+    await grantAllowance(f, other.publicKey, 1_000_000, 60);
+    await warp(60);
+    await expectError(withdrawAsDelegate(f, other, 1), "AllowanceExpired");
+  });
+
+  it("counts open allowances and blocks closing the vault until they are revoked", async () => {
+    const f = await createVault();
+    await depositLamports(f, 10_000_000);
+    const [alice, bob] = [newWallet(), newWallet()];
+    const aliceAllowance = await grantAllowance(f, alice.publicKey, 1_000_000);
+    const bobAllowance = await grantAllowance(f, bob.publicKey, 1_000_000);
+    expect((await program.account.vault.fetch(f.vault)).openAllowances).to.equal(2);
+
+    await revokeAllowance(f, aliceAllowance);
+    expect(await exists(aliceAllowance)).to.equal(false);
+    expect((await program.account.vault.fetch(f.vault)).openAllowances).to.equal(1);
+    await expectError(withdrawAsDelegate(f, alice, 1), "AccountNotInitialized");
+    await expectError(closeVault(f), "VaultInUse");
+
+    await revokeAllowance(f, bobAllowance);
+    expect((await program.account.vault.fetch(f.vault)).openAllowances).to.equal(0);
+  });
+
+  it("closes only with the guardian, no tracked mints and nothing above rent", async () => {
+    const f = await createVault(TOKEN_WINDOW_LIMIT);
+    await depositLamports(f, 1_000_000);
+
+    await expectError(closeVault(f, Keypair.generate()), "Unauthorized");
+    await expectError(closeVault(f), "VaultInUse");
+
+    // The mint can only be dropped once its token account is empty.
+    await expectError(removeMint(f), "MintNotEmpty");
+    await withdrawToken(f, TOKEN_WINDOW_LIMIT);
+    await removeMint(f);
+    expect(await exists(f.mintBalance)).to.equal(false);
+    expect(await exists(f.vaultTokens)).to.equal(false);
+    expect((await program.account.vault.fetch(f.vault)).openMints).to.equal(0);
+
+    // Lamports above rent have to go out through the capped withdrawal first.
+    await expectError(closeVault(f), "VaultNotEmpty");
+    await program.methods
+      .withdraw(new BN(1_000_000))
+      .accountsStrict({ vault: f.vault, authority: f.authority.publicKey })
+      .signers([f.authority])
+      .rpc();
+    const before = await lamports(f.authority.publicKey);
+    const rent = await lamports(f.vault);
+    await closeVault(f);
+    expect(await exists(f.vault)).to.equal(false);
+    expect(await lamports(f.authority.publicKey)).to.equal(before + rent);
+  });
+});
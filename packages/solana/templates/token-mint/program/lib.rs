@@ -7,24 +7,79 @@ declare_id!("TokenMint1111111111111111111111111111111");
 pub mod token_mint {
     use super::*;
 
-    pub fn create_mint(ctx: Context<CreateMint>) -> Result<()> {
+    pub fn create_mint(ctx: Context<CreateMint>, max_supply: u64, wallet_cap: u64) -> Result<()> {
+        require!(
+            max_supply > 0 && wallet_cap > 0 && wallet_cap <= max_supply,
+            ErrorCode::InvalidLimits
+        );
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.mint = ctx.accounts.mint.key();
+        config.max_supply = max_supply;
+        config.wallet_cap = wallet_cap;
+        config.total_minted = 0;
+        config.minting_enabled = true;
+        config.bump = ctx.bumps.config;
         msg!("Token mint created: {}", ctx.accounts.mint.key());
         Ok(())
     }
 
+    pub fn set_minting_enabled(ctx: Context<SetMintingEnabled>, enabled: bool) -> Result<()> {
+        ctx.accounts.config.minting_enabled = enabled;
+        msg!("Minting enabled: {}", enabled);
+        Ok(())
+    }
+
+    pub fn create_mint_record(ctx: Context<CreateMintRecord>) -> Result<()> {
+        let record = &mut ctx.accounts.mint_record;
+        record.mint = ctx.accounts.mint.key();
+        record.wallet = ctx.accounts.wallet.key();
+        record.minted = 0;
+        record.bump = ctx.bumps.mint_record;
+        Ok(())
+    }
+
     pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(config.minting_enabled, ErrorCode::MintingDisabled);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let total_minted = config
+            .total_minted
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(total_minted <= config.max_supply, ErrorCode::MaxSupplyExceeded);
+        let wallet_minted = ctx
+            .accounts
+            .mint_record
+            .minted
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(wallet_minted <= config.wallet_cap, ErrorCode::WalletCapExceeded);
+
+        // The config PDA is the mint authority, so new supply always passes the checks above.
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"config", mint_key.as_ref(), &[config.bump]]];
         token::mint_to(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 token::MintTo {
                     mint: ctx.accounts.mint.to_account_info(),
                     to: ctx.accounts.token_account.to_account_info(),
-                    authority: ctx.accounts.mint_authority.to_account_info(),
+                    authority: config.to_account_info(),
                 },
+                signer_seeds,
             ),
             amount,
         )?;
-        msg!("Minted {} tokens", amount);
+
+        ctx.accounts.config.total_minted = total_minted;
+        ctx.accounts.mint_record.minted = wallet_minted;
+        msg!(
+            "Minted {} tokens, wallet total {} of {}",
+            amount,
+            wallet_minted,
+            ctx.accounts.config.wallet_cap
+        );
         Ok(())
     }
 
@@ -49,26 +104,71 @@ pub mod token_mint {
 pub struct CreateMint<'info> {
     #[account(
         init,
-        payer = payer,
+        payer = admin,
         mint::decimals = 9,
-        mint::authority = mint_authority,
+        mint::authority = config,
     )]
     pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + MintConfig::LEN,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, MintConfig>,
     #[account(mut)]
-    pub payer: Signer<'info>,
-    pub mint_authority: Signer<'info>,
+    pub admin: Signer<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct SetMintingEnabled<'info> {
+    #[account(
+        mut,
+        seeds = [b"config", config.mint.as_ref()],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, MintConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMintRecord<'info> {
+    #[account(seeds = [b"config", mint.key().as_ref()], bump = config.bump)]
+    pub config: Account<'info, MintConfig>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + MintRecord::LEN,
+        seeds = [b"mint_record", mint.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub mint_record: Account<'info, MintRecord>,
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct MintTokens<'info> {
+    #[account(mut, seeds = [b"config", mint.key().as_ref()], bump = config.bump)]
+    pub config: Account<'info, MintConfig>,
     #[account(mut)]
     pub mint: Account<'info, Mint>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"mint_record", mint.key().as_ref(), wallet.key().as_ref()],
+        bump = mint_record.bump
+    )]
+    pub mint_record: Account<'info, MintRecord>,
+    #[account(mut, token::mint = mint, token::authority = wallet)]
     pub token_account: Account<'info, TokenAccount>,
-    pub mint_authority: Signer<'info>,
+    pub wallet: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
@@ -82,3 +182,47 @@ pub struct TransferTokens<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[account]
+pub struct MintConfig {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub max_supply: u64,
+    pub wallet_cap: u64,
+    pub total_minted: u64,
+    pub minting_enabled: bool,
+    pub bump: u8,
+}
+
+impl MintConfig {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 1 + 1;
+}
+
+#[account]
+pub struct MintRecord {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    pub minted: u64,
+    pub bump: u8,
+}
+
+impl MintRecord {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Wallet cap must be non-zero and no larger than the max supply")]
+    InvalidLimits,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Minting is disabled")]
+    MintingDisabled,
+    #[msg("Mint would exceed the max supply")]
+    MaxSupplyExceeded,
+    #[msg("Mint would exceed the per-wallet cap")]
+    WalletCapExceeded,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
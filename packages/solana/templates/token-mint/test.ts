@@ -1,23 +1,47 @@
 const INSTRUCTIONS = [
   {
     name: "create_mint",
-    args: [],
+    args: [
+      { name: "max_supply", type: "u64" },
+      { name: "wallet_cap", type: "u64" },
+    ],
     accounts: [
       { name: "mint", isMut: true, isSigner: false },
-      { name: "payer", isMut: true, isSigner: true },
-      { name: "mint_authority", isMut: false, isSigner: true },
+      { name: "config", isMut: true, isSigner: false },
+      { name: "admin", isMut: true, isSigner: true },
       { name: "token_program", isMut: false, isSigner: false },
       { name: "system_program", isMut: false, isSigner: false },
       { name: "rent", isMut: false, isSigner: false },
     ],
   },
+  {
+    name: "set_minting_enabled",
+    args: [{ name: "enabled", type: "bool" }],
+    accounts: [
+      { name: "config", isMut: true, isSigner: false },
+      { name: "admin", isMut: false, isSigner: true },
+    ],
+  },
+  {
+    name: "create_mint_record",
+    args: [],
+    accounts: [
+      { name: "config", isMut: false, isSigner: false },
+      { name: "mint", isMut: false, isSigner: false },
+      { name: "mint_record", isMut: true, isSigner: false },
+      { name: "wallet", isMut: true, isSigner: true },
+      { name: "system_program", isMut: false, isSigner: false },
+    ],
+  },
   {
     name: "mint_tokens",
     args: [{ name: "amount", type: "u64" }],
     accounts: [
+      { name: "config", isMut: true, isSigner: false },
       { name: "mint", isMut: true, isSigner: false },
+      { name: "mint_record", isMut: true, isSigner: false },
       { name: "token_account", isMut: true, isSigner: false },
-      { name: "mint_authority", isMut: false, isSigner: true },
+      { name: "wallet", isMut: false, isSigner: true },
       { name: "token_program", isMut: false, isSigner: false },
     ],
   },
@@ -63,6 +87,23 @@ function findAccount(instruction, accountName) {
   });
 }
 
+function findAccountType(idl, name) {
+  const accounts = Array.isArray(idl && idl.accounts) ? idl.accounts : [];
+  return accounts.find(function (entry) {
+    return norm(entry.name) === norm(name);
+  });
+}
+
+function fieldTypes(idl, name) {
+  const account = findAccountType(idl, name);
+  const fields = Array.isArray(account && account.type && account.type.fields) ? account.type.fields : [];
+  return Object.fromEntries(
+    fields.map(function (field) {
+      return [norm(field.name), typeToString(field.type)];
+    })
+  );
+}
+
 function isMut(entry) {
   return Boolean(entry && (entry.isMut || entry.writable));
 }
@@ -106,10 +147,18 @@ describe("token-mint behavior", () => {
   it("exposes builder APIs for mint lifecycle", () => {
     const methods = program.methods || {};
 
-    const createMintBuilder = methods[snakeToCamel("create_mint")]();
+    const createMintBuilder = methods[snakeToCamel("create_mint")](1000, 10);
     expect(createMintBuilder && createMintBuilder.accounts).to.be.a("function");
     expect(createMintBuilder && createMintBuilder.rpc).to.be.a("function");
 
+    const setMintingBuilder = methods[snakeToCamel("set_minting_enabled")](false);
+    expect(setMintingBuilder && setMintingBuilder.accounts).to.be.a("function");
+    expect(setMintingBuilder && setMintingBuilder.rpc).to.be.a("function");
+
+    const createRecordBuilder = methods[snakeToCamel("create_mint_record")]();
+    expect(createRecordBuilder && createRecordBuilder.accounts).to.be.a("function");
+    expect(createRecordBuilder && createRecordBuilder.rpc).to.be.a("function");
+
     const mintTokensBuilder = methods[snakeToCamel("mint_tokens")](1);
     expect(mintTokensBuilder && mintTokensBuilder.accounts).to.be.a("function");
     expect(mintTokensBuilder && mintTokensBuilder.rpc).to.be.a("function");
@@ -118,4 +167,30 @@ describe("token-mint behavior", () => {
     expect(transferBuilder && transferBuilder.accounts).to.be.a("function");
     expect(transferBuilder && transferBuilder.rpc).to.be.a("function");
   });
+
+  it("stores supply limits and per-wallet totals in program accounts", () => {
+    const config = fieldTypes(program.idl, "MintConfig");
+    expect(["publicKey", "pubkey"]).to.include(config[norm("admin")]);
+    expect(["publicKey", "pubkey"]).to.include(config[norm("mint")]);
+    expect(config[norm("max_supply")]).to.equal("u64");
+    expect(config[norm("wallet_cap")]).to.equal("u64");
+    expect(config[norm("total_minted")]).to.equal("u64");
+    expect(config[norm("minting_enabled")]).to.equal("bool");
+    expect(config[norm("bump")]).to.equal("u8");
+
+    const record = fieldTypes(program.idl, "MintRecord");
+    expect(["publicKey", "pubkey"]).to.include(record[norm("mint")]);
+    expect(["publicKey", "pubkey"]).to.include(record[norm("wallet")]);
+    expect(record[norm("minted")]).to.equal("u64");
+    expect(record[norm("bump")]).to.equal("u8");
+  });
+
+  it("exposes errors for each minting limit", () => {
+    const errors = (program.idl.errors || []).map(function (entry) {
+      return norm(entry.name);
+    });
+    ["Unauthorized", "InvalidLimits", "MintingDisabled", "MaxSupplyExceeded", "WalletCapExceeded"].forEach(function (name) {
+      expect(errors, "Missing error " + name).to.include(norm(name));
+    });
+  });
 });